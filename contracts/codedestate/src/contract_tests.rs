@@ -2,17 +2,35 @@
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, CosmosMsg, DepsMut, Empty, Response, StdError, WasmMsg,
+    coin, from_binary, to_binary, Addr, BankMsg, CosmosMsg, Decimal, Deps, DepsMut, Empty,
+    Response, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 
 use cw721::{
-    Approval, ApprovalResponse, ContractInfoResponse, Cw721Query, Cw721ReceiveMsg, Expiration,
-    NftInfoResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
+    AddOn, AddOnPricing, AddOnRefund, Approval, ApprovalResponse, AuctionInfoResponse, AuctionKind,
+    CancellationItem, CheckRoyaltiesResponse, ContractInfoResponse, Cw721Query, Cw721ReceiveMsg,
+    Expiration, NftInfoResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
+    RoyaltiesInfoResponse, TokensResponse,
 };
 use cw_ownable::OwnershipError;
 
+use crate::msg::{
+    AvailabilityResponse, BlocklistResponse, ClaimableResponse, DenomTotals, ListingTemplateInfo,
+    ListingTemplatesResponse, MetadataHistoryResponse, MintMsg, OffersResponse, PayoutMsg,
+    PayoutShareMsg, RatingResponse, ReservationQuoteResponse, RoleAssignment, RoyaltyMsg,
+    ShortTermListingMsg, TokenHistoryResponse, TokenRolesResponse, TreasuryTotalsResponse,
+    WithdrawalsResponse, MAX_BATCH_SIZE, REVIEW_WINDOW,
+};
+use crate::property::{
+    GeoCoordinates, PropertyAddress, PropertyExtension, PropertyType, PropertyUpdate,
+};
+use crate::state::{
+    Fraction, GuestAttestation, ListingTemplate, MintPolicy, PayoutConfig, PayoutShare, TokenRole,
+    Verification, Voucher, VoucherDiscount, WithdrawalProposal, WithdrawalStatus,
+};
 use crate::{
-    ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg,
+    ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, Metadata, MinterResponse,
+    PropertyMetadata, QueryMsg,
 };
 
 const MINTER: &str = "merlin";
@@ -32,6 +50,40 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
     contract
 }
 
+/// Mints `token_id` to `host` and lists it for short term stays with auto approval
+fn mint_and_list(mut deps: DepsMut<'_>, token_id: &str, host: &str, price_per_day: u64) {
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    contract
+        .execute(
+            deps.branch(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: String::from(token_id),
+                owner: String::from(host),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps,
+            mock_env(),
+            mock_info(host, &[]),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: String::from(token_id),
+                denom: String::from("unibi"),
+                price_per_day,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 1,
+                cancellation: vec![],
+            },
+        )
+        .unwrap();
+}
+
 #[test]
 fn proper_instantiation() {
     let mut deps = mock_dependencies();
@@ -66,7 +118,9 @@ fn proper_instantiation() {
     assert_eq!(0, count.count);
 
     // list the token_ids
-    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
+    let tokens = contract
+        .all_tokens(deps.as_ref(), String::new(), None, None)
+        .unwrap();
     assert_eq!(0, tokens.tokens.len());
 }
 
@@ -144,7 +198,9 @@ fn minting() {
     assert_eq!(err, ContractError::Claimed {});
 
     // list the token_ids
-    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
+    let tokens = contract
+        .all_tokens(deps.as_ref(), String::new(), None, None)
+        .unwrap();
     assert_eq!(1, tokens.tokens.len());
     assert_eq!(vec![token_id], tokens.tokens);
 }
@@ -294,7 +350,9 @@ fn burning() {
         .unwrap_err();
 
     // list the token_ids
-    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
+    let tokens = contract
+        .all_tokens(deps.as_ref(), String::new(), None, None)
+        .unwrap();
     assert!(tokens.tokens.is_empty());
 }
 
@@ -598,11 +656,18 @@ fn approving_all_revoking_all() {
         .unwrap();
 
     // paginate the token_ids
-    let tokens = contract.all_tokens(deps.as_ref(), None, Some(1)).unwrap();
+    let tokens = contract
+        .all_tokens(deps.as_ref(), String::new(), None, Some(1))
+        .unwrap();
     assert_eq!(1, tokens.tokens.len());
     assert_eq!(vec![token_id1.clone()], tokens.tokens);
     let tokens = contract
-        .all_tokens(deps.as_ref(), Some(token_id1.clone()), Some(3))
+        .all_tokens(
+            deps.as_ref(),
+            String::new(),
+            Some(token_id1.clone()),
+            Some(3),
+        )
         .unwrap();
     assert_eq!(1, tokens.tokens.len());
     assert_eq!(vec![token_id2.clone()], tokens.tokens);
@@ -882,13 +947,22 @@ fn query_tokens_by_owner() {
 
     // get all tokens in order:
     let expected = vec![token_id1.clone(), token_id2.clone(), token_id3.clone()];
-    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
+    let tokens = contract
+        .all_tokens(deps.as_ref(), String::new(), None, None)
+        .unwrap();
     assert_eq!(&expected, &tokens.tokens);
     // paginate
-    let tokens = contract.all_tokens(deps.as_ref(), None, Some(2)).unwrap();
+    let tokens = contract
+        .all_tokens(deps.as_ref(), String::new(), None, Some(2))
+        .unwrap();
     assert_eq!(&expected[..2], &tokens.tokens[..]);
     let tokens = contract
-        .all_tokens(deps.as_ref(), Some(expected[1].clone()), None)
+        .all_tokens(
+            deps.as_ref(),
+            String::new(),
+            Some(expected[1].clone()),
+            None,
+        )
         .unwrap();
     assert_eq!(&expected[2..], &tokens.tokens[..]);
 
@@ -913,3 +987,4171 @@ fn query_tokens_by_owner() {
         .unwrap();
     assert_eq!(&by_demeter[1..], &tokens.tokens[..]);
 }
+
+#[test]
+fn treasury_withdrawals_are_timelocked_and_multisig() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    contract
+        .treasury_deposit(deps.as_mut().storage, &coin(1000, "unibi"))
+        .unwrap();
+
+    let config_msg = ExecuteMsg::UpdateTreasuryConfig {
+        timelock: 3600,
+        instant_limits: vec![coin(100, "unibi")],
+        signers: vec![String::from("alice"), String::from("bob")],
+        threshold: 2,
+    };
+
+    // only the owner can configure the treasury
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            config_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    let owner = mock_info(MINTER, &[]);
+
+    // signers need a threshold they can actually reach
+    for threshold in [0, 3] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                owner.clone(),
+                ExecuteMsg::UpdateTreasuryConfig {
+                    timelock: 3600,
+                    instant_limits: vec![coin(100, "unibi")],
+                    signers: vec![String::from("alice"), String::from("bob")],
+                    threshold,
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidInput {});
+    }
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), config_msg)
+        .unwrap();
+
+    // large amounts can no longer be withdrawn directly
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        target: String::from("vault"),
+        amount: coin(500, "unibi"),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), withdraw_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ProposalRequired {});
+
+    let propose_msg = ExecuteMsg::ProposeWithdrawal {
+        target: String::from("vault"),
+        amount: coin(500, "unibi"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, propose_msg)
+        .unwrap();
+
+    // only signers can approve, and only once
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            ExecuteMsg::ApproveWithdrawal { id: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotSigner {});
+    let alice = mock_info("alice", &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::ApproveWithdrawal { id: 1 },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            alice,
+            ExecuteMsg::ApproveWithdrawal { id: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ApprovedAlready {});
+
+    // the timelock has to pass first
+    let bob = mock_info("bob", &[]);
+    let executable_at = mock_env().block.time.seconds() + 3600;
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            bob.clone(),
+            ExecuteMsg::ExecuteWithdrawal { id: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Timelocked { executable_at });
+
+    // and enough signers have to approve
+    let mut later = mock_env();
+    later.block.time = later.block.time.plus_seconds(3600);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            later.clone(),
+            bob.clone(),
+            ExecuteMsg::ExecuteWithdrawal { id: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientApprovals {
+            approvals: 1,
+            threshold: 2
+        }
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            later.clone(),
+            bob.clone(),
+            ExecuteMsg::ApproveWithdrawal { id: 1 },
+        )
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            later.clone(),
+            bob.clone(),
+            ExecuteMsg::ExecuteWithdrawal { id: 1 },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("vault"),
+            amount: vec![coin(500, "unibi")],
+        })
+    );
+
+    // executed withdrawals cannot run twice
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            later.clone(),
+            bob,
+            ExecuteMsg::ExecuteWithdrawal { id: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::WithdrawalNotPending {});
+
+    let proposal: WithdrawalProposal = from_binary(
+        &contract
+            .query(deps.as_ref(), later.clone(), QueryMsg::Withdrawal { id: 1 })
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(proposal.status, WithdrawalStatus::Executed);
+    assert_eq!(proposal.approvals.len(), 2);
+
+    let res: WithdrawalsResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                later,
+                QueryMsg::Withdrawals {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.withdrawals, vec![proposal]);
+
+    // a timelock that overflows the block time is refused
+    let owner = mock_info(MINTER, &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::UpdateTreasuryConfig {
+                timelock: u64::MAX,
+                instant_limits: vec![],
+                signers: vec![String::from("alice")],
+                threshold: 1,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::ProposeWithdrawal {
+                target: String::from("vault"),
+                amount: coin(100, "unibi"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+}
+
+#[test]
+fn withdraw_decrements_treasury_balance() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    contract
+        .treasury_deposit(deps.as_mut().storage, &coin(1000, "unibi"))
+        .unwrap();
+
+    let owner = mock_info(MINTER, &[]);
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        target: String::from("vault"),
+        amount: coin(600, "unibi"),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            withdraw_msg.clone(),
+        )
+        .unwrap();
+    let event = &res.events[0];
+    assert_eq!(event.ty, "treasury");
+    assert!(event
+        .attributes
+        .iter()
+        .any(|a| a.key == "balance" && a.value == "400"));
+
+    // the same balance cannot be withdrawn twice
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, withdraw_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::UnavailableAmount {});
+
+    // underflow is reported instead of panicking
+    let err = contract
+        .treasury_withdraw(deps.as_mut().storage, &coin(500, "unibi"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::UnavailableAmount {});
+
+    let res: TreasuryTotalsResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TreasuryTotals {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.totals,
+        vec![DenomTotals {
+            denom: String::from("unibi"),
+            balance: Uint128::new(400),
+            deposited: Uint128::new(1000),
+            withdrawn: Uint128::new(600),
+        }]
+    );
+}
+
+#[test]
+fn rental_proceeds_follow_payout_preferences() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let host = mock_info("host", &[]);
+    let token_id = String::from("house");
+
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+
+    let book_and_finalize = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, day: u64| {
+        let start = mock_env().block.time.seconds() + day * 86400;
+        let renting_period = vec![start.to_string(), (start + 2 * 86400).to_string()];
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("traveler", &[coin(200, "unibi")]),
+                ExecuteMsg::SetReservationForShortTerm {
+                    token_id: token_id.clone(),
+                    renting_period: renting_period.clone(),
+                    guests: None,
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds((day + 3) * 86400);
+        contract
+            .execute(
+                deps.as_mut(),
+                env,
+                mock_info("host", &[]),
+                ExecuteMsg::FinalizeShortTermRental {
+                    token_id: token_id.clone(),
+                    traveler: String::from("traveler"),
+                    renting_period,
+                },
+            )
+            .unwrap()
+    };
+
+    // shares have to add up to 100
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetHostPayout {
+                payout: Some(PayoutMsg {
+                    shares: vec![PayoutShareMsg {
+                        address: String::from("alice"),
+                        percentage: 90,
+                    }],
+                    hold: false,
+                }),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidPayout {});
+
+    // without preferences the owner gets everything
+    let res = book_and_finalize(&mut deps, 1);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: vec![coin(200, "unibi")],
+        })]
+    );
+
+    // the host default splits between co-hosts
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetHostPayout {
+                payout: Some(PayoutMsg {
+                    shares: vec![
+                        PayoutShareMsg {
+                            address: String::from("alice"),
+                            percentage: 70,
+                        },
+                        PayoutShareMsg {
+                            address: String::from("bob"),
+                            percentage: 30,
+                        },
+                    ],
+                    hold: false,
+                }),
+            },
+        )
+        .unwrap();
+    let res = book_and_finalize(&mut deps, 10);
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("alice"),
+                amount: vec![coin(140, "unibi")],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("bob"),
+                amount: vec![coin(60, "unibi")],
+            }),
+        ]
+    );
+
+    // a token level config wins over the host default and can hold funds for claiming
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetTokenPayout {
+                token_id: token_id.clone(),
+                payout: Some(PayoutMsg {
+                    shares: vec![PayoutShareMsg {
+                        address: String::from("manager"),
+                        percentage: 100,
+                    }],
+                    hold: true,
+                }),
+            },
+        )
+        .unwrap();
+    let res = book_and_finalize(&mut deps, 20);
+    assert!(res.messages.is_empty());
+
+    let claimable: ClaimableResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Claimable {
+                    address: String::from("manager"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(claimable.amounts, vec![coin(200, "unibi")]);
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[]),
+            ExecuteMsg::ClaimPayout {
+                denom: String::from("unibi"),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("manager"),
+            amount: vec![coin(200, "unibi")],
+        })]
+    );
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[]),
+            ExecuteMsg::ClaimPayout {
+                denom: String::from("unibi"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    // the token config does not survive a transfer
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host,
+            ExecuteMsg::TransferNft {
+                recipient: String::from("buyer"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    let payout: PayoutConfig = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Payout { token_id })
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        payout,
+        PayoutConfig {
+            shares: vec![PayoutShare {
+                address: Addr::unchecked("buyer"),
+                percentage: 100,
+            }],
+            hold: false,
+        }
+    );
+}
+
+#[test]
+fn token_roles_scope_delegated_actions() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let host = mock_info("host", &[]);
+    let manager = mock_info("manager", &[]);
+    let token_id = String::from("house");
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+
+    let list_msg = ExecuteMsg::SetListForShortTermRental {
+        token_id: token_id.clone(),
+        denom: String::from("unibi"),
+        price_per_day: 100,
+        auto_approve: false,
+        available_period: vec![],
+        minimum_stay: 1,
+        cancellation: vec![],
+    };
+    let metadata_msg = ExecuteMsg::SetMetadata {
+        token_id: token_id.clone(),
+        token_uri: String::from("ipfs://house"),
+    };
+
+    // nothing granted yet
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), manager.clone(), list_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole {});
+
+    // only the owner can hand out roles
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            manager.clone(),
+            ExecuteMsg::GrantRole {
+                token_id: token_id.clone(),
+                grantee: String::from("manager"),
+                role: TokenRole::PricingManager,
+                expires: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::GrantRole {
+                token_id: token_id.clone(),
+                grantee: String::from("manager"),
+                role: TokenRole::PricingManager,
+                expires: Some(Expiration::AtHeight(mock_env().block.height + 10)),
+            },
+        )
+        .unwrap();
+
+    // the role covers pricing but nothing else
+    contract
+        .execute(deps.as_mut(), mock_env(), manager.clone(), list_msg.clone())
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), manager.clone(), metadata_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            manager.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("manager"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    // grants run out
+    let mut env = mock_env();
+    env.block.height += 10;
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            manager.clone(),
+            list_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole {});
+
+    let res: TokenRolesResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                env,
+                QueryMsg::TokenRoles {
+                    token_id: token_id.clone(),
+                    include_expired: Some(true),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.roles,
+        vec![RoleAssignment {
+            grantee: String::from("manager"),
+            role: TokenRole::PricingManager,
+            expires: Expiration::AtHeight(mock_env().block.height + 10),
+        }]
+    );
+
+    // roles do not survive a transfer
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host,
+            ExecuteMsg::TransferNft {
+                recipient: String::from("buyer"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    let res: TokenRolesResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TokenRoles {
+                    token_id,
+                    include_expired: Some(true),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(res.roles.is_empty());
+}
+
+#[test]
+fn token_spenders_can_finalize_stays() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("villa");
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    let check_in = mock_env().block.time.seconds() + 86400;
+    let renting_period = vec![check_in.to_string(), (check_in + 86400).to_string()];
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(100, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: renting_period.clone(),
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::Approve {
+                spender: String::from("spender"),
+                token_id: token_id.clone(),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(check_in + 2 * 86400);
+    let finalize = ExecuteMsg::FinalizeShortTermRental {
+        token_id,
+        traveler: String::from("traveler"),
+        renting_period,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("stranger", &[]),
+            finalize.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole {});
+    contract
+        .execute(deps.as_mut(), env, mock_info("spender", &[]), finalize)
+        .unwrap();
+}
+
+#[test]
+fn mint_policy_controls_who_can_mint() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let issuer = mock_info("issuer", &[]);
+    let mint_msg = |token_id: &str| ExecuteMsg::Mint {
+        token_id: token_id.to_string(),
+        owner: String::from("host"),
+        token_uri: None,
+        extension: None,
+    };
+
+    // owner only by default
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), issuer.clone(), mint_msg("1"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    // allowlisted issuers can mint
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateMintPolicy {
+                policy: MintPolicy::Allowlist,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), issuer.clone(), mint_msg("1"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateMinters {
+                add: vec![String::from("issuer")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), issuer, mint_msg("1"))
+        .unwrap();
+
+    // tokens go to the requested owner
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), String::from("1"), false)
+        .unwrap();
+    assert_eq!(owner.owner, "host");
+
+    let minter: MinterResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Minter {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        minter,
+        MinterResponse {
+            minter: Some(MINTER.to_string()),
+            policy: MintPolicy::Allowlist,
+            allowed_minters: vec![String::from("issuer")],
+        }
+    );
+
+    // open minting charges the fee into the treasury
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateMintPolicy {
+                policy: MintPolicy::Open {
+                    fee: Some(coin(50, "unibi")),
+                },
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[coin(49, "unibi")]),
+            mint_msg("2"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientDeposit {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[coin(50, "unibi")]),
+            mint_msg("2"),
+        )
+        .unwrap();
+    assert_eq!(
+        contract
+            .get_balance(deps.as_ref().storage, String::from("unibi"))
+            .unwrap(),
+        Uint128::new(50)
+    );
+}
+
+#[test]
+fn listing_requires_verification_when_enabled() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info(MINTER, &[]);
+    let host = mock_info("host", &[]);
+    let verifier = mock_info("registry", &[]);
+    let token_id = String::from("house");
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetRequireVerification { required: true },
+        )
+        .unwrap();
+
+    let list_msg = ExecuteMsg::SetListForShortTermRental {
+        token_id: token_id.clone(),
+        denom: String::from("unibi"),
+        price_per_day: 100,
+        auto_approve: false,
+        available_period: vec![],
+        minimum_stay: 1,
+        cancellation: vec![],
+    };
+    let verify_msg = ExecuteMsg::VerifyProperty {
+        token_id: token_id.clone(),
+        attestation_uri: String::from("ejari://12345"),
+    };
+
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), host.clone(), list_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotVerified {});
+
+    // only appointed verifiers can attest
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            verifier.clone(),
+            verify_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotVerifier {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::UpdateVerifiers {
+                add: vec![String::from("registry")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), verifier.clone(), verify_msg)
+        .unwrap();
+
+    let verification: Option<Verification> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Verification {
+                    token_id: token_id.clone(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        verification,
+        Some(Verification {
+            verifier: Addr::unchecked("registry"),
+            attestation_uri: String::from("ejari://12345"),
+            verified_at: mock_env().block.time.seconds(),
+        })
+    );
+    contract
+        .execute(deps.as_mut(), mock_env(), host.clone(), list_msg.clone())
+        .unwrap();
+    let save_template = |price_per_day: u64, propagate: bool| ExecuteMsg::SaveListingTemplate {
+        template_id: String::from("standard"),
+        template: ListingTemplate {
+            denom: String::from("unibi"),
+            price_per_day,
+            auto_approve: true,
+            minimum_stay: 1,
+            cancellation: vec![],
+        },
+        propagate,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            save_template(120, false),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::ApplyListingTemplate {
+                template_id: String::from("standard"),
+                token_ids: vec![token_id.clone()],
+            },
+        )
+        .unwrap();
+
+    // after revocation the token cannot be listed again
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            verifier,
+            ExecuteMsg::RevokeVerification {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), host.clone(), list_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotVerified {});
+
+    // not even by updating its template
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), host, save_template(150, true))
+        .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "skipped" && attr.value == token_id));
+    let token = contract.tokens.load(&deps.storage, &token_id).unwrap();
+    assert_eq!(token.shortterm_rental.price_per_day, 120);
+}
+
+#[test]
+fn transfers_carry_reservations_and_settle_earned_rent() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("house");
+    let day = |n: u64| mock_env().block.time.seconds() + n * 86400;
+    let env_at = |n: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(n * 86400);
+        env
+    };
+
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::SetFeeValue { fee: 1000 },
+        )
+        .unwrap();
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+
+    // an ongoing four day stay and a future two day stay, rent plus 10% fee each
+    let ongoing = vec![day(1).to_string(), day(5).to_string()];
+    let future = vec![day(10).to_string(), day(12).to_string()];
+    for (traveler, period, deposit) in [("alice", &ongoing, 440), ("bob", &future, 220)] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(traveler, &[coin(deposit, "unibi")]),
+                ExecuteMsg::SetReservationForShortTerm {
+                    token_id: token_id.clone(),
+                    renting_period: period.clone(),
+                    guests: None,
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .unwrap();
+    }
+
+    // plain transfers and sends are blocked while bookings exist
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env_at(3),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("buyer"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::OutstandingReservations {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env_at(3),
+            mock_info("host", &[]),
+            ExecuteMsg::SendNft {
+                contract: String::from("market"),
+                token_id: token_id.clone(),
+                msg: to_binary("sell").unwrap(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::OutstandingReservations {});
+
+    // half of the ongoing stay is settled to the seller, minus the fee
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env_at(3),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferNftWithReservations {
+                recipient: String::from("buyer"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: vec![coin(180, "unibi")],
+        })]
+    );
+    assert_eq!(
+        contract
+            .get_balance(deps.as_ref().storage, String::from("unibi"))
+            .unwrap(),
+        Uint128::new(80)
+    );
+
+    // the buyer receives the rest of the stay at finalize
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env_at(6),
+            mock_info("buyer", &[]),
+            ExecuteMsg::FinalizeShortTermRental {
+                token_id: token_id.clone(),
+                traveler: String::from("alice"),
+                renting_period: ongoing,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("buyer"),
+            amount: vec![coin(180, "unibi")],
+        })]
+    );
+
+    // nothing is earned yet on the future booking, which moves along with the send
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env_at(6),
+            mock_info("buyer", &[]),
+            ExecuteMsg::SendNftWithReservations {
+                contract: String::from("market"),
+                token_id: token_id.clone(),
+                msg: to_binary("sell").unwrap(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let rental = contract
+        .nft_shorttermrental_info(deps.as_ref(), token_id)
+        .unwrap();
+    assert_eq!(rental.travelers.len(), 1);
+    assert_eq!(rental.travelers[0].deposit_amount, Uint128::new(200));
+}
+
+#[test]
+fn burn_is_blocked_while_funds_are_escrowed() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let host = mock_info("host", &[]);
+    let token_id = String::from("house");
+    let start = mock_env().block.time.seconds() + 86400;
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: token_id.clone(),
+                denom: String::from("unibi"),
+                price_per_day: 100,
+                auto_approve: false,
+                available_period: vec![],
+                minimum_stay: 1,
+                cancellation: vec![],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[coin(200, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: vec![start.to_string(), (start + 2 * 86400).to_string()],
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::Burn {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::EscrowedFunds {});
+
+    // the emergency path is reserved to the contract owner
+    let emergency_msg = ExecuteMsg::EmergencyBurn {
+        token_id: token_id.clone(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), host, emergency_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            emergency_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("alice"),
+            amount: vec![coin(200, "unibi")],
+        })]
+    );
+    contract.nft_info(deps.as_ref(), token_id).unwrap_err();
+    assert_eq!(0, contract.num_tokens(deps.as_ref()).unwrap().count);
+}
+
+#[test]
+fn emergency_burn_settles_bookings_and_spares_shareholders() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("house");
+    let day = |n: u64| mock_env().block.time.seconds() + n * 86400;
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: token_id.clone(),
+                denom: String::from("unibi"),
+                price_per_day: 100,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 1,
+                cancellation: vec![CancellationItem {
+                    deadline: 0,
+                    percentage: 50,
+                }],
+            },
+        )
+        .unwrap();
+    for (traveler, check_in) in [("alice", 1), ("bob", 5)] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(traveler, &[coin(200, "unibi")]),
+                ExecuteMsg::SetReservationForShortTerm {
+                    token_id: token_id.clone(),
+                    renting_period: vec![day(check_in).to_string(), day(check_in + 2).to_string()],
+                    guests: None,
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .unwrap();
+    }
+    // bob gets half back, the other half is the host's
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            ExecuteMsg::CancelRentalForShortterm {
+                token_id: token_id.clone(),
+                renting_period: vec![day(5).to_string(), day(7).to_string()],
+            },
+        )
+        .unwrap();
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::EmergencyBurn {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: vec![coin(100, "unibi")],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("alice"),
+                amount: vec![coin(200, "unibi")],
+            }),
+        ]
+    );
+    contract.nft_info(deps.as_ref(), token_id).unwrap_err();
+
+    mint_and_list(deps.as_mut(), "flat", "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::Fractionalize {
+                token_id: String::from("flat"),
+                shares: Uint128::new(100),
+                buyout_price: coin(10000, "unibi"),
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::EmergencyBurn {
+                token_id: String::from("flat"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Fractionalized {});
+}
+
+#[test]
+fn royalties_use_token_or_default_share() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info(MINTER, &[]);
+
+    for token_id in ["1", "2"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                owner.clone(),
+                ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: String::from("host"),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+
+    // no royalty configured
+    let info = contract
+        .royalty_info(deps.as_ref(), String::from("1"), Uint128::new(1000))
+        .unwrap();
+    assert_eq!(
+        info,
+        RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: Uint128::zero(),
+        }
+    );
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetDefaultRoyalty {
+                royalty: Some(RoyaltyMsg {
+                    payment_address: String::from("developer"),
+                    share: 10001,
+                }),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRoyalty {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetDefaultRoyalty { royalty: None },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetDefaultRoyalty {
+                royalty: Some(RoyaltyMsg {
+                    payment_address: String::from("developer"),
+                    share: 500,
+                }),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::SetTokenRoyalty {
+                token_id: String::from("2"),
+                royalty: Some(RoyaltyMsg {
+                    payment_address: String::from("platform"),
+                    share: 250,
+                }),
+            },
+        )
+        .unwrap();
+
+    let info: RoyaltiesInfoResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RoyaltyInfo {
+                    token_id: String::from("1"),
+                    sale_price: Uint128::new(1000),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        info,
+        RoyaltiesInfoResponse {
+            address: String::from("developer"),
+            royalty_amount: Uint128::new(50),
+        }
+    );
+    let info = contract
+        .royalty_info(deps.as_ref(), String::from("2"), Uint128::new(1000))
+        .unwrap();
+    assert_eq!(
+        info,
+        RoyaltiesInfoResponse {
+            address: String::from("platform"),
+            royalty_amount: Uint128::new(25),
+        }
+    );
+
+    let check: CheckRoyaltiesResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::CheckRoyalties {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(check.royalty_payments);
+}
+
+#[test]
+fn royalties_give_way_to_the_fee() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info(MINTER, &[]);
+    let token_id = String::from("house");
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetFeeValue { fee: 10001 },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetFeeValue { fee: 2000 },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetDefaultRoyalty {
+                royalty: Some(RoyaltyMsg {
+                    payment_address: String::from("developer"),
+                    share: 10000,
+                }),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::ListForSale {
+                token_id: token_id.clone(),
+                price: coin(1000, "unibi"),
+            },
+        )
+        .unwrap();
+
+    // fee and royalty add up to 120%, the royalty only gets what the fee leaves
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[coin(1000, "unibi")]),
+            ExecuteMsg::Buy {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("developer"),
+            amount: vec![coin(800, "unibi")],
+        })]
+    );
+    assert_eq!(
+        contract
+            .owner_of(deps.as_ref(), mock_env(), token_id, false)
+            .unwrap()
+            .owner,
+        "buyer"
+    );
+}
+
+#[test]
+fn sales_and_offers_pay_fee_royalty_and_seller() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info(MINTER, &[]);
+    let token_id = String::from("house");
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetFeeValue { fee: 500 },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetDefaultRoyalty {
+                royalty: Some(RoyaltyMsg {
+                    payment_address: String::from("developer"),
+                    share: 1000,
+                }),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::ListForSale {
+                token_id: token_id.clone(),
+                price: coin(1000, "unibi"),
+            },
+        )
+        .unwrap();
+
+    let buy_msg = ExecuteMsg::Buy {
+        token_id: token_id.clone(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[coin(900, "unibi")]),
+            buy_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidDeposit {});
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[coin(1000, "unibi")]),
+            buy_msg.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("developer"),
+                amount: vec![coin(100, "unibi")],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: vec![coin(850, "unibi")],
+            }),
+        ]
+    );
+    assert_eq!(
+        contract
+            .owner_of(deps.as_ref(), mock_env(), token_id.clone(), false)
+            .unwrap()
+            .owner,
+        "buyer"
+    );
+    // the listing went with the sale
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[coin(1000, "unibi")]),
+            buy_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotForSale {});
+
+    // offers are escrowed, a new offer refunds the previous one
+    let offer = |buyer: &str, amount: u128| {
+        (
+            mock_info(buyer, &[coin(amount, "unibi")]),
+            ExecuteMsg::MakeOffer {
+                token_id: token_id.clone(),
+                expires: None,
+            },
+        )
+    };
+    for (info, msg) in [offer("carol", 500), offer("dave", 600)] {
+        contract
+            .execute(deps.as_mut(), mock_env(), info, msg)
+            .unwrap();
+    }
+    let (info, msg) = offer("dave", 700);
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), info, msg)
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("dave"),
+            amount: vec![coin(600, "unibi")],
+        })]
+    );
+
+    let accept_msg = ExecuteMsg::AcceptOffer {
+        token_id: token_id.clone(),
+        buyer: String::from("dave"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("carol", &[]),
+            accept_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[]),
+            accept_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("developer"),
+                amount: vec![coin(70, "unibi")],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("buyer"),
+                amount: vec![coin(595, "unibi")],
+            }),
+        ]
+    );
+    assert_eq!(
+        contract
+            .get_balance(deps.as_ref().storage, String::from("unibi"))
+            .unwrap(),
+        Uint128::new(85)
+    );
+
+    // the remaining offer is still open and can be withdrawn
+    let res: OffersResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Offers {
+                    token_id: token_id.clone(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.offers.len(), 1);
+    assert_eq!(res.offers[0].buyer, Addr::unchecked("carol"));
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("carol", &[]),
+            ExecuteMsg::CancelOffer { token_id },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("carol"),
+            amount: vec![coin(500, "unibi")],
+        })]
+    );
+}
+
+#[test]
+fn auctions_refund_outbid_bidders_and_sell_through_transfer() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info(MINTER, &[]);
+    for token_id in ["villa", "cabin"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                owner.clone(),
+                ExecuteMsg::Mint {
+                    token_id: String::from(token_id),
+                    owner: String::from("host"),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+    let at = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env
+    };
+
+    // an auction has to end at a time the chain can reach
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::StartAuction {
+                token_id: String::from("villa"),
+                kind: AuctionKind::Dutch { floor_price: 500 },
+                denom: String::from("unibi"),
+                price: 1000,
+                duration: u64::MAX,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+
+    // English auction over an hour with a reserve of 1000
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::StartAuction {
+                token_id: String::from("villa"),
+                kind: AuctionKind::English {
+                    reserve_price: 1000,
+                    min_increment: 100,
+                    extension: 600,
+                },
+                denom: String::from("unibi"),
+                price: 500,
+                duration: 3600,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("friend"),
+                token_id: String::from("villa"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::AuctionActive {});
+
+    let bid = |bidder: &str, amount: u128| {
+        (
+            mock_info(bidder, &[coin(amount, "unibi")]),
+            ExecuteMsg::PlaceBid {
+                token_id: String::from("villa"),
+            },
+        )
+    };
+    let (info, msg) = bid("alice", 600);
+    contract.execute(deps.as_mut(), at(100), info, msg).unwrap();
+    let (info, msg) = bid("bob", 650);
+    let err = contract
+        .execute(deps.as_mut(), at(200), info, msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::BidTooLow { min_bid: 700 });
+
+    // a late bid refunds the previous bidder and extends the auction
+    let (info, msg) = bid("bob", 1200);
+    let res = contract
+        .execute(deps.as_mut(), at(3500), info, msg)
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("alice"),
+            amount: vec![coin(600, "unibi")],
+        })]
+    );
+    let auction: AuctionInfoResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                at(3500),
+                QueryMsg::Auction {
+                    token_id: String::from("villa"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(auction.end_time, at(4100).block.time.seconds());
+    assert_eq!(auction.price, 1300);
+
+    let settle_msg = ExecuteMsg::SettleAuction {
+        token_id: String::from("villa"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(3700),
+            mock_info("anyone", &[]),
+            settle_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::AuctionNotEnded {});
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            at(4100),
+            mock_info("anyone", &[]),
+            settle_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: vec![coin(1200, "unibi")],
+        })]
+    );
+    assert_eq!(
+        contract
+            .owner_of(deps.as_ref(), mock_env(), String::from("villa"), false)
+            .unwrap()
+            .owner,
+        "bob"
+    );
+
+    // Dutch auction decaying from 1000 to 400 over 600 seconds
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::StartAuction {
+                token_id: String::from("cabin"),
+                kind: AuctionKind::Dutch { floor_price: 400 },
+                denom: String::from("unibi"),
+                price: 1000,
+                duration: 600,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(300),
+            mock_info("carol", &[coin(600, "unibi")]),
+            ExecuteMsg::PlaceBid {
+                token_id: String::from("cabin"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::BidTooLow { min_bid: 700 });
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            at(300),
+            mock_info("carol", &[coin(750, "unibi")]),
+            ExecuteMsg::PlaceBid {
+                token_id: String::from("cabin"),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("host"),
+                amount: vec![coin(700, "unibi")],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("carol"),
+                amount: vec![coin(50, "unibi")],
+            }),
+        ]
+    );
+    assert_eq!(
+        contract
+            .owner_of(deps.as_ref(), mock_env(), String::from("cabin"), false)
+            .unwrap()
+            .owner,
+        "carol"
+    );
+}
+
+#[test]
+fn fractionalized_tokens_share_rent_and_redeem_buyouts() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("house");
+
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::Fractionalize {
+                token_id: token_id.clone(),
+                shares: Uint128::new(100),
+                buyout_price: coin(10000, "unibi"),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferShares {
+                token_id: token_id.clone(),
+                recipient: String::from("investor"),
+                amount: Uint128::new(25),
+            },
+        )
+        .unwrap();
+
+    // the token is locked while fractionalized
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("friend"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Fractionalized {});
+    // and so can't be auctioned or sold either, neither could ever settle
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::StartAuction {
+                token_id: token_id.clone(),
+                kind: AuctionKind::Dutch { floor_price: 500 },
+                denom: String::from("unibi"),
+                price: 1000,
+                duration: 86400,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Fractionalized {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::ListForSale {
+                token_id: token_id.clone(),
+                price: coin(1000, "unibi"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Fractionalized {});
+
+    // a token listed for sale has to be delisted before it is split up
+    mint_and_list(deps.as_mut(), "flat", "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::ListForSale {
+                token_id: String::from("flat"),
+                price: coin(1000, "unibi"),
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::Fractionalize {
+                token_id: String::from("flat"),
+                shares: Uint128::new(100),
+                buyout_price: coin(10000, "unibi"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ListedForSale {});
+
+    // finalized rent is credited to the shareholders instead of paid out
+    let start = mock_env().block.time.seconds() + 86400;
+    let renting_period = vec![start.to_string(), (start + 2 * 86400).to_string()];
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(200, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: renting_period.clone(),
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(4 * 86400);
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info("host", &[]),
+            ExecuteMsg::FinalizeShortTermRental {
+                token_id: token_id.clone(),
+                traveler: String::from("traveler"),
+                renting_period,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("investor", &[]),
+            ExecuteMsg::ClaimDividends {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("investor"),
+            amount: vec![coin(50, "unibi")],
+        })]
+    );
+
+    // a buyout hands over the token and makes the payment redeemable
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("investor", &[]),
+            ExecuteMsg::RedeemShares {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotBoughtOut {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[coin(10000, "unibi")]),
+            ExecuteMsg::Buyout {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        contract
+            .owner_of(deps.as_ref(), mock_env(), token_id.clone(), false)
+            .unwrap()
+            .owner,
+        "buyer"
+    );
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::RedeemShares {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: vec![coin(7650, "unibi")],
+        })]
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("investor", &[]),
+            ExecuteMsg::RedeemShares {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    let fraction: Option<Fraction> = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Fraction { token_id })
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(fraction, None);
+}
+
+#[test]
+fn dividends_carry_rounding_and_stay_in_the_buyout_denom() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("cabin");
+    let relist = |token_id: &str, denom: &str| ExecuteMsg::SetListForShortTermRental {
+        token_id: String::from(token_id),
+        denom: String::from(denom),
+        price_per_day: 100,
+        auto_approve: true,
+        available_period: vec![],
+        minimum_stay: 1,
+        cancellation: vec![],
+    };
+    let fractionalize = |token_id: &str| ExecuteMsg::Fractionalize {
+        token_id: String::from(token_id),
+        shares: Uint128::new(3),
+        buyout_price: coin(900, "unibi"),
+    };
+
+    // rent in another denom could never reach the shareholders
+    mint_and_list(deps.as_mut(), "loft", "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            relist("loft", "uusdc"),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            fractionalize("loft"),
+        )
+        .unwrap_err();
+    let mismatch = ContractError::DenomMismatch {
+        denom: String::from("unibi"),
+    };
+    assert_eq!(err, mismatch);
+
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            fractionalize(&token_id),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            relist(&token_id, "uusdc"),
+        )
+        .unwrap_err();
+    assert_eq!(err, mismatch);
+
+    // 100 doesn't split into three shares, the remainder is kept for the next stay
+    let start = mock_env().block.time.seconds() + 86400;
+    let mut later = mock_env();
+    later.block.time = later.block.time.plus_seconds(4 * 86400);
+    let fraction = |deps: Deps| -> Fraction {
+        from_binary(
+            &contract
+                .query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::Fraction {
+                        token_id: token_id.clone(),
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    for night in 0..2 {
+        let renting_period = vec![
+            (start + night * 86400).to_string(),
+            (start + (night + 1) * 86400).to_string(),
+        ];
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("traveler", &[coin(100, "unibi")]),
+                ExecuteMsg::SetReservationForShortTerm {
+                    token_id: token_id.clone(),
+                    renting_period: renting_period.clone(),
+                    guests: None,
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .unwrap();
+        contract
+            .execute(
+                deps.as_mut(),
+                later.clone(),
+                mock_info("host", &[]),
+                ExecuteMsg::FinalizeShortTermRental {
+                    token_id: token_id.clone(),
+                    traveler: String::from("traveler"),
+                    renting_period,
+                },
+            )
+            .unwrap();
+        if night == 0 {
+            assert_eq!(fraction(deps.as_ref()).undistributed, Uint128::new(1));
+        }
+    }
+    assert_eq!(fraction(deps.as_ref()).undistributed, Uint128::zero());
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::ClaimDividends {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("host"),
+            amount: vec![coin(200, "unibi")],
+        })]
+    );
+}
+
+#[test]
+fn property_metadata_is_validated_and_searchable() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let property = |city: &str, property_type: PropertyType, max_guests: u32| PropertyMetadata {
+        address: PropertyAddress {
+            street: String::from("1 Marina Walk"),
+            city: String::from(city),
+            region: None,
+            postal_code: None,
+            country: String::from("AE"),
+        },
+        coordinates: Some(GeoCoordinates {
+            latitude_e6: 25_080_000,
+            longitude_e6: 55_140_000,
+        }),
+        property_type,
+        bedrooms: 2,
+        bathrooms: 1,
+        max_guests,
+        amenities: vec![String::from("wifi")],
+        house_rules: vec![],
+        check_in_time: String::from("15:00"),
+        check_out_time: String::from("11:00"),
+        photos: vec![String::from("ipfs://cover")],
+    };
+    let mint = |token_id: &str, property: PropertyMetadata| ExecuteMsg::Mint {
+        token_id: String::from(token_id),
+        owner: String::from("host"),
+        token_uri: None,
+        extension: Some(Metadata {
+            property: Some(property),
+            ..Metadata::default()
+        }),
+    };
+
+    let mut invalid = property("Dubai", PropertyType::Apartment, 4);
+    invalid.check_in_time = String::from("25:00");
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint("broken", invalid),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidPropertyMetadata {
+            field: String::from("check_in_time"),
+        }
+    );
+
+    for (token_id, property) in [
+        ("marina", property("Dubai", PropertyType::Apartment, 4)),
+        ("palm", property(" dubai", PropertyType::Villa, 8)),
+        (
+            "old-town",
+            property("Abu Dhabi", PropertyType::Apartment, 4),
+        ),
+    ] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                mint(token_id, property),
+            )
+            .unwrap();
+    }
+
+    let search = |deps: &cosmwasm_std::OwnedDeps<_, _, _>,
+                  property_type: Option<PropertyType>,
+                  min_guests: Option<u32>| {
+        let res: TokensResponse = from_binary(
+            &contract
+                .query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::SearchProperties {
+                        city: String::from("DUBAI"),
+                        property_type,
+                        min_guests,
+                        min_bedrooms: None,
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap();
+        res.tokens
+    };
+    assert_eq!(search(&deps, None, None), vec!["marina", "palm"]);
+    assert_eq!(
+        search(&deps, Some(PropertyType::Apartment), None),
+        vec!["marina"]
+    );
+    assert_eq!(search(&deps, None, Some(6)), vec!["palm"]);
+
+    let stored: Option<PropertyMetadata> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Property {
+                    token_id: String::from("palm"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(stored.unwrap().address.city, " dubai");
+}
+
+#[test]
+fn extension_updates_are_checked_and_versioned() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("loft");
+    let property = PropertyMetadata {
+        address: PropertyAddress {
+            street: String::from("5 Canal Street"),
+            city: String::from("Amsterdam"),
+            region: None,
+            postal_code: Some(String::from("1012")),
+            country: String::from("NL"),
+        },
+        coordinates: None,
+        property_type: PropertyType::Apartment,
+        bedrooms: 1,
+        bathrooms: 1,
+        max_guests: 2,
+        amenities: vec![],
+        house_rules: vec![],
+        check_in_time: String::from("14:00"),
+        check_out_time: String::from("10:00"),
+        photos: vec![],
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: Some(Metadata {
+                    description: Some(String::from("Cosy lfot")),
+                    property: Some(property),
+                    ..Metadata::default()
+                }),
+            },
+        )
+        .unwrap();
+
+    let update = |max_guests: u32| ExecuteMsg::UpdateProperty {
+        token_id: token_id.clone(),
+        update: PropertyUpdate {
+            amenities: Some(vec![String::from("wifi"), String::from("kitchen")]),
+            max_guests: Some(max_guests),
+            ..PropertyUpdate::default()
+        },
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            update(3),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole {});
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("host", &[]), update(0))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidPropertyMetadata {
+            field: String::from("max_guests"),
+        }
+    );
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("host", &[]), update(3))
+        .unwrap();
+
+    // a full replacement keeps what the host sends, here fixing the description
+    let mut extension = contract
+        .nft_info(deps.as_ref(), token_id.clone())
+        .unwrap()
+        .extension;
+    let property = extension.property().unwrap().clone();
+    assert_eq!(property.max_guests, 3);
+    assert_eq!(property.bedrooms, 1);
+    extension.as_mut().unwrap().description = Some(String::from("Cosy loft"));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::UpdateExtension {
+                token_id: token_id.clone(),
+                extension,
+            },
+        )
+        .unwrap();
+
+    let history: MetadataHistoryResponse<Extension> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::MetadataHistory {
+                    token_id,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    let descriptions: Vec<_> = history
+        .versions
+        .iter()
+        .map(|version| {
+            (
+                version.version,
+                version.editor.as_str(),
+                version.extension.as_ref().unwrap().description.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            (1, MINTER, Some(String::from("Cosy lfot"))),
+            (2, "host", Some(String::from("Cosy lfot"))),
+            (3, "host", Some(String::from("Cosy loft"))),
+        ]
+    );
+    assert_eq!(
+        history.versions[1].extension.property().unwrap().amenities,
+        vec!["wifi", "kitchen"]
+    );
+}
+
+#[test]
+fn token_history_records_the_life_of_a_property() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("house");
+
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    let start = mock_env().block.time.seconds() + 86400;
+    let renting_period = vec![start.to_string(), (start + 86400).to_string()];
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(100, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: renting_period.clone(),
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3 * 86400);
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("host", &[]),
+            ExecuteMsg::FinalizeShortTermRental {
+                token_id: token_id.clone(),
+                traveler: String::from("traveler"),
+                renting_period,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetMetadata {
+                token_id: token_id.clone(),
+                token_uri: String::from("ipfs://house"),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("buyer"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info("buyer", &[]),
+            ExecuteMsg::Burn {
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+
+    // the log outlives the token
+    let history = |start_after: Option<u64>, limit: Option<u32>| -> TokenHistoryResponse {
+        from_binary(
+            &contract
+                .query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::TokenHistory {
+                        token_id: token_id.clone(),
+                        start_after,
+                        limit,
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let kinds: Vec<_> = history(None, None)
+        .entries
+        .into_iter()
+        .map(|entry| (entry.seq, entry.event.ty))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (1, String::from("mint")),
+            (2, String::from("metadata")),
+            (3, String::from("listing")),
+            (4, String::from("stay_completed")),
+            (5, String::from("metadata")),
+            (6, String::from("transfer")),
+            (7, String::from("burn")),
+        ]
+    );
+
+    let page = history(Some(5), Some(1));
+    assert_eq!(page.entries.len(), 1);
+    let transfer = &page.entries[0].event;
+    assert_eq!(transfer.attributes[0].value, "host");
+    assert_eq!(transfer.attributes[1].value, "buyer");
+}
+
+#[test]
+fn batches_are_capped_and_atomic() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let mints = |ids: &[&str]| ExecuteMsg::BatchMint {
+        mints: ids
+            .iter()
+            .map(|id| MintMsg {
+                token_id: id.to_string(),
+                owner: String::from("manager"),
+                token_uri: None,
+                extension: None,
+            })
+            .collect(),
+    };
+
+    let too_many: Vec<String> = (0..=MAX_BATCH_SIZE).map(|i| i.to_string()).collect();
+    let ids: Vec<&str> = too_many.iter().map(String::as_str).collect();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mints(&ids),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE
+        }
+    );
+
+    // open minting charges the fee once per token
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateMintPolicy {
+                policy: MintPolicy::Open {
+                    fee: Some(coin(50, "unibi")),
+                },
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[coin(100, "unibi")]),
+            mints(&["unit-1", "unit-2", "unit-3"]),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientDeposit {});
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[coin(150, "unibi")]),
+            mints(&["unit-1", "unit-2", "unit-3"]),
+        )
+        .unwrap();
+    let processed: Vec<_> = res
+        .attributes
+        .iter()
+        .filter(|attr| attr.key == "token_id")
+        .map(|attr| attr.value.as_str())
+        .collect();
+    assert_eq!(processed, vec!["unit-1", "unit-2", "unit-3"]);
+    assert_eq!(
+        contract
+            .get_balance(deps.as_ref().storage, String::from("unibi"))
+            .unwrap(),
+        Uint128::new(150)
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[]),
+            ExecuteMsg::BatchSetListing {
+                listings: ["unit-1", "unit-2"]
+                    .iter()
+                    .map(|id| ShortTermListingMsg {
+                        token_id: id.to_string(),
+                        denom: String::from("unibi"),
+                        price_per_day: 120,
+                        auto_approve: true,
+                        available_period: vec![],
+                        minimum_stay: 1,
+                        cancellation: vec![],
+                    })
+                    .collect(),
+            },
+        )
+        .unwrap();
+    let listed = contract.tokens.load(&deps.storage, "unit-2").unwrap();
+    assert_eq!(listed.shortterm_rental.price_per_day, 120);
+
+    // one token the sender cannot move fails the whole batch
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: String::from("other"),
+                owner: String::from("someone"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    let transfer = |ids: &[&str]| ExecuteMsg::BatchTransfer {
+        recipient: String::from("owner"),
+        token_ids: ids.iter().map(|id| id.to_string()).collect(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[]),
+            transfer(&["other", "unit-1"]),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[]),
+            transfer(&["unit-1", "unit-3"]),
+        )
+        .unwrap();
+    assert_eq!(
+        contract
+            .owner_of(deps.as_ref(), mock_env(), String::from("unit-3"), false)
+            .unwrap()
+            .owner,
+        "owner"
+    );
+}
+
+#[test]
+fn listing_templates_apply_and_propagate_to_idle_units() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    for token_id in ["apt-1", "apt-2", "apt-3"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                ExecuteMsg::Mint {
+                    token_id: String::from(token_id),
+                    owner: String::from("host"),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+    let template = |price_per_day: u64| ListingTemplate {
+        denom: String::from("unibi"),
+        price_per_day,
+        auto_approve: true,
+        minimum_stay: 2,
+        cancellation: vec![],
+    };
+    let save = |price_per_day: u64| ExecuteMsg::SaveListingTemplate {
+        template_id: String::from("standard"),
+        template: template(price_per_day),
+        propagate: true,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("host", &[]), save(100))
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::ApplyListingTemplate {
+                template_id: String::from("standard"),
+                token_ids: vec![
+                    String::from("apt-1"),
+                    String::from("apt-2"),
+                    String::from("apt-3"),
+                ],
+            },
+        )
+        .unwrap();
+
+    // apt-2 gets booked and apt-3 is repriced by hand, which unlinks it
+    let start = mock_env().block.time.seconds() + 86400;
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(200, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: String::from("apt-2"),
+                renting_period: vec![start.to_string(), (start + 2 * 86400).to_string()],
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: String::from("apt-3"),
+                denom: String::from("unibi"),
+                price_per_day: 90,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 1,
+                cancellation: vec![],
+            },
+        )
+        .unwrap();
+
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("host", &[]), save(130))
+        .unwrap();
+    let attrs: Vec<_> = res
+        .attributes
+        .iter()
+        .skip(3)
+        .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+        .collect();
+    assert_eq!(attrs, vec![("token_id", "apt-1"), ("skipped", "apt-2")]);
+    let price = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, token_id: &str| {
+        contract
+            .tokens
+            .load(&deps.storage, token_id)
+            .unwrap()
+            .shortterm_rental
+            .price_per_day
+    };
+    assert_eq!(price(&deps, "apt-1"), 130);
+    assert_eq!(price(&deps, "apt-2"), 100);
+    assert_eq!(price(&deps, "apt-3"), 90);
+
+    let templates: ListingTemplatesResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ListingTemplates {
+                    host: String::from("host"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        templates.templates,
+        vec![ListingTemplateInfo {
+            template_id: String::from("standard"),
+            template: template(130),
+            token_ids: vec![String::from("apt-1"), String::from("apt-2")],
+        }]
+    );
+}
+
+#[test]
+fn stay_rules_limit_reservations_and_availability() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("cabin");
+    mint_and_list(deps.as_mut(), &token_id, "host", 10);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[]),
+            ExecuteMsg::SetStayRules {
+                token_id: token_id.clone(),
+                maximum_stay: 7,
+                booking_horizon: 30,
+                advance_notice: 86400,
+                buffer_days: 1,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole {});
+    // rules only apply to a listing, they would be lost when it gets listed
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: String::from("shed"),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetStayRules {
+                token_id: String::from("shed"),
+                maximum_stay: 7,
+                booking_horizon: 30,
+                advance_notice: 86400,
+                buffer_days: 1,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotListed {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetStayRules {
+                token_id: token_id.clone(),
+                maximum_stay: 7,
+                booking_horizon: 30,
+                advance_notice: 86400,
+                buffer_days: 1,
+            },
+        )
+        .unwrap();
+
+    let now = mock_env().block.time.seconds();
+    let day = 86400;
+    let availability = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, check_in: u64, length: u64| {
+        let res: AvailabilityResponse = from_binary(
+            &contract
+                .query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::CheckAvailability {
+                        token_id: String::from("cabin"),
+                        renting_period: vec![check_in.to_string(), (check_in + length).to_string()],
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap();
+        res.reason
+    };
+    let reason = |err: ContractError| Some(err.to_string());
+    assert_eq!(
+        availability(&deps, now + 3600, 2 * day),
+        reason(ContractError::InsufficientNotice {})
+    );
+    assert_eq!(
+        availability(&deps, now + 31 * day, 2 * day),
+        reason(ContractError::BeyondBookingHorizon {})
+    );
+    assert_eq!(
+        availability(&deps, now + 2 * day, 8 * day),
+        reason(ContractError::MoreThanMaximum {})
+    );
+    // a started night counts as a whole one
+    assert_eq!(
+        availability(&deps, now + 2 * day, 7 * day + 3600),
+        reason(ContractError::MoreThanMaximum {})
+    );
+    assert_eq!(availability(&deps, now + 2 * day, 3 * day), None);
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(30, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: vec![(now + 2 * day).to_string(), (now + 5 * day).to_string()],
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+    // a day of cleaning has to be left on either side of the stay
+    assert_eq!(
+        availability(&deps, now + 6 * day, 2 * day),
+        reason(ContractError::UnavailablePeriod {})
+    );
+    assert_eq!(availability(&deps, now + 6 * day + 1, 2 * day), None);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(20, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id,
+                renting_period: vec![(now + 6 * day).to_string(), (now + 8 * day).to_string()],
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::UnavailablePeriod {});
+}
+
+#[test]
+fn stay_periods_must_be_ordered_upcoming_and_within_bounds() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("cabin");
+    mint_and_list(deps.as_mut(), &token_id, "host", 10);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: token_id.clone(),
+                denom: String::from("unibi"),
+                price_per_day: 10,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 0,
+                cancellation: vec![],
+            },
+        )
+        .unwrap();
+
+    let now = mock_env().block.time.seconds();
+    let day = 86400;
+    let availability = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, check_in: u64, check_out: u64| {
+        let res: AvailabilityResponse = from_binary(
+            &contract
+                .query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::CheckAvailability {
+                        token_id: String::from("cabin"),
+                        renting_period: vec![check_in.to_string(), check_out.to_string()],
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap();
+        res.reason
+    };
+    let reason = |err: ContractError| Some(err.to_string());
+    let invalid = reason(ContractError::InvalidInput {});
+    assert_eq!(availability(&deps, now + 3 * day, now + 2 * day), invalid);
+    assert_eq!(availability(&deps, now + 2 * day, now + 2 * day), invalid);
+    // no notice required still means no stays in the past
+    assert_eq!(
+        availability(&deps, now - day, now + day),
+        reason(ContractError::InsufficientNotice {})
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(20, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: vec![(now + 2 * day).to_string(), (now + 4 * day).to_string()],
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(20, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: vec![(now + 8 * day).to_string(), (now + 6 * day).to_string()],
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+
+    // huge rules saturate instead of overflowing
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetStayRules {
+                token_id,
+                maximum_stay: 0,
+                booking_horizon: u64::MAX,
+                advance_notice: 0,
+                buffer_days: u64::MAX,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        availability(&deps, now + 100 * day, now + 102 * day),
+        reason(ContractError::UnavailablePeriod {})
+    );
+}
+
+#[test]
+fn extra_guests_are_priced_and_capped_by_occupancy() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("loft");
+    let property = PropertyMetadata {
+        address: PropertyAddress {
+            street: String::from("8 Canal Street"),
+            city: String::from("Amsterdam"),
+            region: None,
+            postal_code: None,
+            country: String::from("NL"),
+        },
+        coordinates: None,
+        property_type: PropertyType::Apartment,
+        bedrooms: 2,
+        bathrooms: 1,
+        max_guests: 4,
+        amenities: vec![],
+        house_rules: vec![],
+        check_in_time: String::from("15:00"),
+        check_out_time: String::from("11:00"),
+        photos: vec![],
+    };
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::SetFeeValue { fee: 1000 },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: Some(Metadata {
+                    property: Some(property),
+                    ..Metadata::default()
+                }),
+            },
+        )
+        .unwrap();
+    let host = mock_info("host", &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: token_id.clone(),
+                denom: String::from("unibi"),
+                price_per_day: 100,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 1,
+                cancellation: vec![],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host,
+            ExecuteMsg::SetGuestPricing {
+                token_id: token_id.clone(),
+                base_guests: 2,
+                extra_guest_fee: 20,
+            },
+        )
+        .unwrap();
+
+    let start = mock_env().block.time.seconds() + 86400;
+    let renting_period = vec![start.to_string(), (start + 3 * 86400).to_string()];
+    // three nights of 100 plus two extra guests at 20 a night, and the 10% fee
+    let quote: ReservationQuoteResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QuoteReservation {
+                    token_id: token_id.clone(),
+                    renting_period: renting_period.clone(),
+                    guests: Some(4),
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        quote,
+        ReservationQuoteResponse {
+            denom: String::from("unibi"),
+            nights: 3,
+            guests: 4,
+            rent: Uint128::new(420),
+            extra_guest_fee: Uint128::new(120),
+            add_ons: vec![],
+            discount: Uint128::zero(),
+            deposit: Uint128::new(420),
+            fee: Uint128::new(42),
+            total: Uint128::new(462),
+        }
+    );
+
+    let reserve = |guests: u32| ExecuteMsg::SetReservationForShortTerm {
+        token_id: token_id.clone(),
+        renting_period: renting_period.clone(),
+        guests: Some(guests),
+        add_ons: vec![],
+        voucher: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(1000, "unibi")]),
+            reserve(5),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TooManyGuests { max_guests: 4 });
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(461, "unibi")]),
+            reserve(4),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientDeposit {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(462, "unibi")]),
+            reserve(4),
+        )
+        .unwrap();
+    let traveler = contract
+        .tokens
+        .load(&deps.storage, &token_id)
+        .unwrap()
+        .shortterm_rental
+        .travelers
+        .remove(0);
+    assert_eq!(traveler.deposit_amount, Uint128::new(420));
+    assert_eq!(traveler.guests, 4);
+
+    // without included guests even the first one pays the fee
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetGuestPricing {
+                token_id: token_id.clone(),
+                base_guests: 0,
+                extra_guest_fee: 20,
+            },
+        )
+        .unwrap();
+    let quote: ReservationQuoteResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QuoteReservation {
+                    token_id,
+                    renting_period: vec![
+                        (start + 4 * 86400).to_string(),
+                        (start + 7 * 86400).to_string(),
+                    ],
+                    guests: Some(1),
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(quote.extra_guest_fee, Uint128::new(60));
+    assert_eq!(quote.rent, Uint128::new(360));
+}
+
+#[test]
+fn partial_nights_are_charged_in_full() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("studio");
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: token_id.clone(),
+                denom: String::from("unibi"),
+                price_per_day: 100,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 0,
+                cancellation: vec![],
+            },
+        )
+        .unwrap();
+
+    let start = mock_env().block.time.seconds() + 86400;
+    let quote = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, hours: u64| {
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QuoteReservation {
+                    token_id: token_id.clone(),
+                    renting_period: vec![start.to_string(), (start + hours * 3600).to_string()],
+                    guests: None,
+                    add_ons: vec![],
+                    voucher: None,
+                },
+            )
+            .and_then(|res| from_binary::<ReservationQuoteResponse>(&res))
+    };
+    let quoted = quote(&deps, 36).unwrap();
+    assert_eq!((quoted.nights, quoted.rent), (2, Uint128::new(200)));
+    let quoted = quote(&deps, 12).unwrap();
+    assert_eq!((quoted.nights, quoted.total), (1, Uint128::new(100)));
+    quote(&deps, 0).unwrap_err();
+
+    // a stay shorter than a night still pays for one
+    let reserve = ExecuteMsg::SetReservationForShortTerm {
+        token_id: token_id.clone(),
+        renting_period: vec![start.to_string(), (start + 12 * 3600).to_string()],
+        guests: None,
+        add_ons: vec![],
+        voucher: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(99, "unibi")]),
+            reserve.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientDeposit {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(100, "unibi")]),
+            reserve,
+        )
+        .unwrap();
+}
+
+#[test]
+fn add_ons_are_escrowed_and_refunded_by_their_own_rule() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("chalet");
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: String::from("host"),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    let host = mock_info("host", &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetListForShortTermRental {
+                token_id: token_id.clone(),
+                denom: String::from("unibi"),
+                price_per_day: 100,
+                auto_approve: true,
+                available_period: vec![],
+                minimum_stay: 1,
+                cancellation: vec![CancellationItem {
+                    deadline: 5,
+                    percentage: 50,
+                }],
+            },
+        )
+        .unwrap();
+    let add_on = |id: &str, price: u64, pricing, mandatory, refund| AddOn {
+        id: String::from(id),
+        price,
+        pricing,
+        mandatory,
+        refund,
+    };
+    let add_ons = vec![
+        add_on("cleaning", 50, AddOnPricing::Flat, true, AddOnRefund::None),
+        add_on(
+            "pet",
+            10,
+            AddOnPricing::PerNight,
+            false,
+            AddOnRefund::Policy,
+        ),
+        add_on("pickup", 30, AddOnPricing::Flat, false, AddOnRefund::Full),
+    ];
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::SetAddOns {
+                token_id: token_id.clone(),
+                add_ons: vec![add_ons[0].clone(), add_ons[0].clone()],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host,
+            ExecuteMsg::SetAddOns {
+                token_id: token_id.clone(),
+                add_ons,
+            },
+        )
+        .unwrap();
+
+    let start = mock_env().block.time.seconds() + 10 * 86400;
+    let renting_period = vec![start.to_string(), (start + 3 * 86400).to_string()];
+    let reserve = |add_ons: &[&str]| ExecuteMsg::SetReservationForShortTerm {
+        token_id: token_id.clone(),
+        renting_period: renting_period.clone(),
+        guests: None,
+        add_ons: add_ons.iter().map(|id| id.to_string()).collect(),
+        voucher: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(1000, "unibi")]),
+            reserve(&["spa"]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownAddOn {
+            id: String::from("spa")
+        }
+    );
+    // 300 rent, 50 cleaning, 30 for the pet and 30 for the pickup
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(409, "unibi")]),
+            reserve(&["pet", "pickup"]),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientDeposit {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(410, "unibi")]),
+            reserve(&["pet", "pickup"]),
+        )
+        .unwrap();
+
+    // half of the rent and the pet fee come back, the pickup in full, the cleaning not at all
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[]),
+            ExecuteMsg::CancelRentalForShortterm {
+                token_id: token_id.clone(),
+                renting_period,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("traveler"),
+            amount: vec![coin(195, "unibi")],
+        })]
+    );
+    let traveler = contract
+        .tokens
+        .load(&deps.storage, &token_id)
+        .unwrap()
+        .shortterm_rental
+        .travelers
+        .remove(0);
+    assert!(traveler.cancelled);
+    assert_eq!(traveler.deposit_amount, Uint128::new(215));
+}
+
+#[test]
+fn vouchers_discount_the_rent_within_their_limits() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("bungalow");
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::SetFeeValue { fee: 1000 },
+        )
+        .unwrap();
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    let host = mock_info("host", &[]);
+
+    let create =
+        |code: &str, discount, max_uses, expires, hosts: Vec<String>| ExecuteMsg::CreateVoucher {
+            code: String::from(code),
+            discount,
+            max_uses,
+            expires,
+            token_ids: vec![],
+            hosts,
+        };
+    let percent_off = VoucherDiscount::Percentage { percent: 10 };
+    // hosts cannot issue codes for someone else's tokens
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            create(
+                "HOST10",
+                percent_off.clone(),
+                None,
+                None,
+                vec![String::from("other")],
+            ),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            create("HOST10", percent_off.clone(), None, None, vec![]),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            create("HOST10", percent_off, None, None, vec![]),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::VoucherExists {});
+    let flat_off = VoucherDiscount::Fixed {
+        amount: Uint128::new(50),
+        denom: String::from("unibi"),
+    };
+    // claiming a code first doesn't take it from anyone else, it stays scoped to the issuer
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("squatter", &[]),
+            create(
+                "FLAT50",
+                VoucherDiscount::Percentage { percent: 1 },
+                None,
+                None,
+                vec![],
+            ),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            create("FLAT50", flat_off, Some(1), None, vec![]),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            create(
+                "OLD",
+                VoucherDiscount::Percentage { percent: 50 },
+                None,
+                Some(Expiration::AtHeight(1)),
+                vec![],
+            ),
+        )
+        .unwrap();
+    let voucher: Option<Voucher> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Voucher {
+                    issuer: String::from("host"),
+                    code: String::from("HOST10"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(voucher.unwrap().hosts, vec![Addr::unchecked("host")]);
+
+    let start = mock_env().block.time.seconds() + 86400;
+    let period = |offset: u64| {
+        let check_in = start + offset * 86400;
+        vec![check_in.to_string(), (check_in + 3 * 86400).to_string()]
+    };
+    let quote = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, code: &str| {
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QuoteReservation {
+                    token_id: String::from("bungalow"),
+                    renting_period: period(0),
+                    guests: None,
+                    add_ons: vec![],
+                    voucher: Some(String::from(code)),
+                },
+            )
+            .map(|res| from_binary::<ReservationQuoteResponse>(&res).unwrap())
+    };
+    // the 10% fee is charged on the discounted rent
+    let quoted = quote(&deps, "HOST10").unwrap();
+    assert_eq!(
+        (quoted.discount, quoted.deposit, quoted.fee, quoted.total),
+        (
+            Uint128::new(30),
+            Uint128::new(270),
+            Uint128::new(27),
+            Uint128::new(297)
+        )
+    );
+    assert_eq!(
+        quote(&deps, "OLD").unwrap_err(),
+        StdError::generic_err(ContractError::VoucherExpired {}.to_string())
+    );
+
+    let reserve = |offset: u64, funds: u128| {
+        (
+            mock_info("traveler", &[coin(funds, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: period(offset),
+                guests: None,
+                add_ons: vec![],
+                voucher: Some(String::from("FLAT50")),
+            },
+        )
+    };
+    let (info, msg) = reserve(0, 274);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), info, msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientDeposit {});
+    let (info, msg) = reserve(0, 275);
+    contract
+        .execute(deps.as_mut(), mock_env(), info, msg)
+        .unwrap();
+    let traveler = contract
+        .tokens
+        .load(&deps.storage, &token_id)
+        .unwrap()
+        .shortterm_rental
+        .travelers
+        .remove(0);
+    assert_eq!(traveler.deposit_amount, Uint128::new(250));
+    let (info, msg) = reserve(10, 275);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), info, msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::VoucherExhausted {});
+
+    // only the issuer or the contract owner can remove a code
+    let remove = ExecuteMsg::RemoveVoucher {
+        code: String::from("FLAT50"),
+        issuer: Some(String::from("squatter")),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), host, remove.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), remove)
+        .unwrap();
+}
+
+#[test]
+fn parties_review_a_stay_once_within_the_window() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("townhouse");
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    let check_in = mock_env().block.time.seconds() + 86400;
+    let check_out = check_in + 2 * 86400;
+    let renting_period = vec![check_in.to_string(), check_out.to_string()];
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("traveler", &[coin(200, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: renting_period.clone(),
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap();
+
+    let at = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    };
+    let review = |rating: u8| ExecuteMsg::ReviewStay {
+        token_id: token_id.clone(),
+        traveler: String::from("traveler"),
+        renting_period: renting_period.clone(),
+        rating,
+        comment_uri: Some(String::from("ipfs://review")),
+    };
+
+    // a stay that is already over can't be booked to review it right away
+    let past = vec![
+        (check_in - 5 * 86400).to_string(),
+        (check_in - 3 * 86400).to_string(),
+    ];
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_in + 86400),
+            mock_info("shill", &[coin(200, "unibi")]),
+            ExecuteMsg::SetReservationForShortTerm {
+                token_id: token_id.clone(),
+                renting_period: past.clone(),
+                guests: None,
+                add_ons: vec![],
+                voucher: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientNotice {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_in + 86400),
+            mock_info("shill", &[]),
+            ExecuteMsg::ReviewStay {
+                token_id: token_id.clone(),
+                traveler: String::from("shill"),
+                renting_period: past,
+                rating: 5,
+                comment_uri: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotReserved {});
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_in),
+            mock_info("traveler", &[]),
+            review(4),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ReviewNotOpen {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_out),
+            mock_info("neighbour", &[]),
+            review(1),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotStayParty {});
+    contract
+        .execute(
+            deps.as_mut(),
+            at(check_out),
+            mock_info("traveler", &[]),
+            review(4),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_out),
+            mock_info("traveler", &[]),
+            review(5),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::AlreadyReviewed {});
+
+    // selling the token afterwards doesn't make the buyer the host of the stay
+    contract
+        .execute(
+            deps.as_mut(),
+            at(check_out + 3600),
+            mock_info("host", &[]),
+            ExecuteMsg::TransferNftWithReservations {
+                recipient: String::from("buyer"),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_out + 7200),
+            mock_info("buyer", &[]),
+            review(1),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotStayParty {});
+
+    // the host can still review the guest after the stay is finalized
+    contract
+        .execute(
+            deps.as_mut(),
+            at(check_out + 86400),
+            mock_info("buyer", &[]),
+            ExecuteMsg::FinalizeShortTermRental {
+                token_id: token_id.clone(),
+                traveler: String::from("traveler"),
+                renting_period: renting_period.clone(),
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at(check_out + REVIEW_WINDOW + 1),
+            mock_info("host", &[]),
+            review(5),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ReviewWindowClosed {});
+    contract
+        .execute(
+            deps.as_mut(),
+            at(check_out + REVIEW_WINDOW),
+            mock_info("host", &[]),
+            review(5),
+        )
+        .unwrap();
+
+    let rating = |msg: QueryMsg<Empty>| -> RatingResponse {
+        from_binary(&contract.query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    };
+    let expected = |count: u64, average: u64| RatingResponse {
+        count,
+        average: Decimal::from_atomics(average, 0).unwrap(),
+    };
+    assert_eq!(rating(QueryMsg::TokenRating { token_id }), expected(1, 4));
+    assert_eq!(
+        rating(QueryMsg::AddressRating {
+            address: String::from("host")
+        }),
+        expected(1, 4)
+    );
+    assert_eq!(
+        rating(QueryMsg::AddressRating {
+            address: String::from("traveler")
+        }),
+        expected(1, 5)
+    );
+}
+
+#[test]
+fn bookings_can_require_guest_attestations() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("penthouse");
+    let provider = mock_info("kyc-provider", &[]);
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    let host = mock_info("host", &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host,
+            ExecuteMsg::SetGuestKycLevel {
+                token_id: token_id.clone(),
+                min_level: 2,
+            },
+        )
+        .unwrap();
+
+    let expires_at = mock_env().block.height + 100;
+    let attest = |level: u8| ExecuteMsg::AttestGuest {
+        address: String::from("traveler"),
+        level,
+        expires: Expiration::AtHeight(expires_at),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), provider.clone(), attest(2))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotKycProvider {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateKycProviders {
+                add: vec![String::from("kyc-provider")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), provider.clone(), attest(1))
+        .unwrap();
+
+    let start = mock_env().block.time.seconds() + 86400;
+    let reserve = |offset: u64| ExecuteMsg::SetReservationForShortTerm {
+        token_id: token_id.clone(),
+        renting_period: vec![
+            (start + offset * 86400).to_string(),
+            (start + (offset + 2) * 86400).to_string(),
+        ],
+        guests: None,
+        add_ons: vec![],
+        voucher: None,
+    };
+    let traveler = mock_info("traveler", &[coin(200, "unibi")]);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), traveler.clone(), reserve(0))
+        .unwrap_err();
+    assert_eq!(err, ContractError::KycRequired { level: 2 });
+    contract
+        .execute(deps.as_mut(), mock_env(), provider, attest(2))
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), traveler.clone(), reserve(0))
+        .unwrap();
+
+    let attestation: Option<GuestAttestation> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Attestation {
+                    address: String::from("traveler"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(attestation.map(|attestation| attestation.level), Some(2));
+
+    // an expired attestation no longer counts
+    let mut env = mock_env();
+    env.block.height = expires_at;
+    let err = contract
+        .execute(deps.as_mut(), env, traveler.clone(), reserve(5))
+        .unwrap_err();
+    assert_eq!(err, ContractError::KycRequired { level: 2 });
+
+    // nor does one from a dismissed provider
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateKycProviders {
+                add: vec![],
+                remove: vec![String::from("kyc-provider")],
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), traveler, reserve(5))
+        .unwrap_err();
+    assert_eq!(err, ContractError::KycRequired { level: 2 });
+}
+
+#[test]
+fn blocked_guests_cannot_book() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let token_id = String::from("cottage");
+    mint_and_list(deps.as_mut(), &token_id, "host", 100);
+    let host = mock_info("host", &[]);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            ExecuteMsg::UpdateHostBlocklist {
+                add: vec![String::from("rowdy")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    let block_fraudster = ExecuteMsg::UpdateGlobalBlocklist {
+        add: vec![String::from("fraudster")],
+        remove: vec![],
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host.clone(),
+            block_fraudster.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            block_fraudster,
+        )
+        .unwrap();
+
+    let blocklist = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, host: Option<&str>| {
+        let res: BlocklistResponse = from_binary(
+            &contract
+                .query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::Blocklist {
+                        host: host.map(String::from),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap();
+        res.addresses
+    };
+    assert_eq!(blocklist(&deps, Some("host")), vec!["rowdy"]);
+    assert_eq!(blocklist(&deps, None), vec!["fraudster"]);
+
+    let start = mock_env().block.time.seconds() + 86400;
+    let reserve = |offset: u64| ExecuteMsg::SetReservationForShortTerm {
+        token_id: token_id.clone(),
+        renting_period: vec![
+            (start + offset * 86400).to_string(),
+            (start + (offset + 2) * 86400).to_string(),
+        ],
+        guests: None,
+        add_ons: vec![],
+        voucher: None,
+    };
+    for guest in ["rowdy", "fraudster"] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(guest, &[coin(200, "unibi")]),
+                reserve(0),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::Blocked {});
+    }
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            host,
+            ExecuteMsg::UpdateHostBlocklist {
+                add: vec![],
+                remove: vec![String::from("rowdy")],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("rowdy", &[coin(200, "unibi")]),
+            reserve(0),
+        )
+        .unwrap();
+}
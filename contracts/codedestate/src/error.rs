@@ -61,12 +61,162 @@ pub enum ContractError {
     #[error("Rental period is too short")]
     LessThanMinimum {},
 
+    #[error("Rental period is too long")]
+    MoreThanMaximum {},
+
+    #[error("Rental period starts too far ahead")]
+    BeyondBookingHorizon {},
+
+    #[error("Rental period starts too soon")]
+    InsufficientNotice {},
+
+    #[error("The property takes at most {max_guests} guests")]
+    TooManyGuests { max_guests: u32 },
+
+    #[error("Unknown add-on {id}")]
+    UnknownAddOn { id: String },
+
+    #[error("Voucher code is already taken")]
+    VoucherExists {},
+
+    #[error("Voucher has expired")]
+    VoucherExpired {},
+
+    #[error("Voucher has no uses left")]
+    VoucherExhausted {},
+
+    #[error("Voucher does not apply to this booking")]
+    VoucherNotApplicable {},
+
+    #[error("Stay cannot be reviewed before checkout")]
+    ReviewNotOpen {},
+
+    #[error("Review window for this stay has closed")]
+    ReviewWindowClosed {},
+
+    #[error("Stay was already reviewed by this party")]
+    AlreadyReviewed {},
+
+    #[error("Sender did not take part in this stay")]
+    NotStayParty {},
+
     #[error("Cannot set approval that is already expired")]
     Expired {},
 
+    #[error("Withdrawal exceeds the instant limit and must be proposed")]
+    ProposalRequired {},
+
+    #[error("Withdrawal is timelocked until {executable_at}")]
+    Timelocked { executable_at: u64 },
+
+    #[error("Withdrawal has {approvals} of {threshold} required approvals")]
+    InsufficientApprovals { approvals: u32, threshold: u32 },
+
+    #[error("Sender is not a treasury signer")]
+    NotSigner {},
+
+    #[error("Withdrawal is not pending")]
+    WithdrawalNotPending {},
+
+    #[error("Payout shares must be positive, distinct and add up to 100")]
+    InvalidPayout {},
+
+    #[error("Token has outstanding reservations, transfer it with its reservations instead")]
+    OutstandingReservations {},
+
+    #[error("Token is not listed for sale")]
+    NotForSale {},
+
+    #[error("Token is listed for sale")]
+    ListedForSale {},
+
+    #[error("Offer not found")]
+    OfferNotFound {},
+
+    #[error("Offer has expired")]
+    OfferExpired {},
+
+    #[error("Batches hold at most {max} items")]
+    BatchTooLarge { max: usize },
+
+    #[error("Token has no property metadata")]
+    MissingPropertyMetadata {},
+
+    #[error("Invalid property metadata: {field}")]
+    InvalidPropertyMetadata { field: String },
+
+    #[error("Token is fractionalized")]
+    Fractionalized {},
+
+    #[error("Token has already been bought out")]
+    BoughtOut {},
+
+    #[error("Token has not been bought out")]
+    NotBoughtOut {},
+
+    #[error("Not enough shares")]
+    InsufficientShares {},
+
+    #[error("Rent of a fractionalized token has to be paid in {denom}")]
+    DenomMismatch { denom: String },
+
+    #[error("Token is being auctioned")]
+    AuctionActive {},
+
+    #[error("Auction has ended")]
+    AuctionEnded {},
+
+    #[error("Auction has not ended yet")]
+    AuctionNotEnded {},
+
+    #[error("Auction already has bids")]
+    AuctionHasBids {},
+
+    #[error("Bid must be at least {min_bid}")]
+    BidTooLow { min_bid: u64 },
+
+    #[error("Royalty share cannot exceed 10000 basis points")]
+    InvalidRoyalty {},
+
+    #[error("Token holds escrowed funds and cannot be burned")]
+    EscrowedFunds {},
+
+    #[error("Sender is not a verifier")]
+    NotVerifier {},
+
+    #[error("Sender is not a KYC provider")]
+    NotKycProvider {},
+
+    #[error("Booking requires a valid identity attestation of level {level}")]
+    KycRequired { level: u8 },
+
+    #[error("Sender is blocked from booking this property")]
+    Blocked {},
+
+    #[error("Token must be verified before it can be listed")]
+    NotVerified {},
+
+    #[error("Sender is not allowed to mint")]
+    NotMinter {},
+
+    #[error("Sender does not have the role required for this action")]
+    MissingRole {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
     #[error("Refundable amount:{amount}")]
     RefundableAmount { amount: String },
 
     #[error("Approval not found for: {spender}")]
     ApprovalNotFound { spender: String },
+
+    #[error("Cannot migrate from contract {contract}")]
+    WrongMigrationContract { contract: String },
+
+    #[error("Cannot migrate from version {from} down to {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Cannot migrate from version {version}")]
+    UnsupportedMigrationVersion { version: String },
 }
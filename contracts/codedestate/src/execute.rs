@@ -4,21 +4,30 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use cosmwasm_std::{
-    Addr, BankMsg, Binary, Coin, CustomMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128,
+    Addr, Api, BankMsg, Binary, Coin, CustomMsg, Decimal, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128,
 };
 
 use cw721::{
-    CancellationItem, ContractInfoResponse, Cw721Execute, Cw721ReceiveMsg, Expiration, Landlord, LongTermRental, ShortTermRental, Tenant, Traveler
+    AddOn, AddOnPricing, AddOnRefund, AuctionKind, Bid, BookedAddOn, CancellationItem, ContractInfoResponse, Cw721Execute, Cw721ReceiveMsg, Expiration, Landlord, ShortTermRental, Tenant, Traveler
 };
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, MintMsg, PayoutMsg, ReservationQuoteResponse, RoyaltyMsg,
+    ShortTermListingMsg, MAX_ADD_ONS, MAX_BATCH_SIZE, REVIEW_WINDOW,
+};
+use crate::property::{PropertyExtension, PropertyUpdate};
+use crate::state::{
+    Approval, Auction, CompletedStay, Cw721Contract, Fraction, GuestAttestation, ListingTemplate,
+    MetadataVersion, MintPolicy, Offer, PayoutConfig, PayoutShare, Review, RoleGrant, Royalty,
+    SaleListing, Settlement, Shareholder, TokenInfo, TokenRole, TreasuryConfig, Verification,
+    Voucher, VoucherDiscount, WithdrawalProposal, WithdrawalStatus,
+};
 
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
@@ -53,12 +62,145 @@ where
                 owner,
                 token_uri,
                 extension,
-            } => self.mint(deps, info, token_id, owner, token_uri, extension),
+            } => self.mint(deps, env, info, token_id, owner, token_uri, extension),
+            ExecuteMsg::BatchMint { mints } => self.batch_mint(deps, env, info, mints),
+            ExecuteMsg::BatchTransfer {
+                recipient,
+                token_ids,
+            } => self.batch_transfer(deps, env, info, recipient, token_ids),
+            ExecuteMsg::BatchSetListing { listings } => {
+                self.batch_set_listing(deps, env, info, listings)
+            }
+            ExecuteMsg::SaveListingTemplate {
+                template_id,
+                template,
+                propagate,
+            } => self.save_listing_template(deps, env, info, template_id, template, propagate),
+            ExecuteMsg::RemoveListingTemplate { template_id } => {
+                self.remove_listing_template(deps, info, template_id)
+            }
+            ExecuteMsg::CreateVoucher {
+                code,
+                discount,
+                max_uses,
+                expires,
+                token_ids,
+                hosts,
+            } => self.create_voucher(
+                deps, info, code, discount, max_uses, expires, token_ids, hosts,
+            ),
+            ExecuteMsg::RemoveVoucher { code, issuer } => {
+                self.remove_voucher(deps, info, code, issuer)
+            }
+            ExecuteMsg::ApplyListingTemplate {
+                template_id,
+                token_ids,
+            } => self.apply_listing_template(deps, env, info, template_id, token_ids),
+            ExecuteMsg::UpdateMintPolicy { policy } => self.update_mint_policy(deps, info, policy),
+            ExecuteMsg::UpdateMinters { add, remove } => {
+                self.update_minters(deps, info, add, remove)
+            }
+            ExecuteMsg::TransferNftWithReservations {
+                recipient,
+                token_id,
+            } => self.transfer_nft_with_reservations(deps, env, info, recipient, token_id),
+            ExecuteMsg::SendNftWithReservations {
+                contract,
+                token_id,
+                msg,
+            } => self.send_nft_with_reservations(deps, env, info, contract, token_id, msg),
+            ExecuteMsg::EmergencyBurn { token_id } => {
+                self.emergency_burn(deps, env, info, token_id)
+            }
+            ExecuteMsg::ListForSale { token_id, price } => {
+                self.list_for_sale(deps, env, info, token_id, price)
+            }
+            ExecuteMsg::DelistFromSale { token_id } => {
+                self.delist_from_sale(deps, env, info, token_id)
+            }
+            ExecuteMsg::Buy { token_id } => self.buy(deps, env, info, token_id),
+            ExecuteMsg::MakeOffer { token_id, expires } => {
+                self.make_offer(deps, env, info, token_id, expires)
+            }
+            ExecuteMsg::AcceptOffer { token_id, buyer } => {
+                self.accept_offer(deps, env, info, token_id, buyer)
+            }
+            ExecuteMsg::CancelOffer { token_id } => self.cancel_offer(deps, info, token_id),
+            ExecuteMsg::Fractionalize {
+                token_id,
+                shares,
+                buyout_price,
+            } => self.fractionalize(deps, env, info, token_id, shares, buyout_price),
+            ExecuteMsg::TransferShares {
+                token_id,
+                recipient,
+                amount,
+            } => self.transfer_shares(deps, info, token_id, recipient, amount),
+            ExecuteMsg::ClaimDividends { token_id } => self.claim_dividends(deps, info, token_id),
+            ExecuteMsg::Buyout { token_id } => self.buyout(deps, env, info, token_id),
+            ExecuteMsg::RedeemShares { token_id } => self.redeem_shares(deps, info, token_id),
+            ExecuteMsg::StartAuction {
+                token_id,
+                kind,
+                denom,
+                price,
+                duration,
+            } => self.start_auction(deps, env, info, token_id, kind, denom, price, duration),
+            ExecuteMsg::PlaceBid { token_id } => self.place_bid(deps, env, info, token_id),
+            ExecuteMsg::SettleAuction { token_id } => {
+                self.settle_auction(deps, env, info, token_id)
+            }
+            ExecuteMsg::CancelAuction { token_id } => {
+                self.cancel_auction(deps, env, info, token_id)
+            }
+            ExecuteMsg::SetDefaultRoyalty { royalty } => {
+                self.set_default_royalty(deps, info, royalty)
+            }
+            ExecuteMsg::SetTokenRoyalty { token_id, royalty } => {
+                self.set_token_royalty(deps, info, token_id, royalty)
+            }
+            ExecuteMsg::UpdateVerifiers { add, remove } => {
+                self.update_verifiers(deps, info, add, remove)
+            }
+            ExecuteMsg::SetRequireVerification { required } => {
+                self.set_require_verification(deps, info, required)
+            }
+            ExecuteMsg::VerifyProperty {
+                token_id,
+                attestation_uri,
+            } => self.verify_property(deps, env, info, token_id, attestation_uri),
+            ExecuteMsg::RevokeVerification { token_id } => {
+                self.revoke_verification(deps, info, token_id)
+            }
+            ExecuteMsg::UpdateKycProviders { add, remove } => {
+                self.update_kyc_providers(deps, info, add, remove)
+            }
+            ExecuteMsg::AttestGuest {
+                address,
+                level,
+                expires,
+            } => self.attest_guest(deps, env, info, address, level, expires),
+            ExecuteMsg::RevokeAttestation { address } => {
+                self.revoke_attestation(deps, info, address)
+            }
+            ExecuteMsg::UpdateGlobalBlocklist { add, remove } => {
+                self.update_global_blocklist(deps, info, add, remove)
+            }
+            ExecuteMsg::UpdateHostBlocklist { add, remove } => {
+                self.update_host_blocklist(deps, info, add, remove)
+            }
 
             ExecuteMsg::SetMetadata {
                 token_id,
                 token_uri,
             } => self.setmetadata(deps, env, info, token_id, token_uri),
+            ExecuteMsg::UpdateExtension {
+                token_id,
+                extension,
+            } => self.update_extension(deps, env, info, token_id, extension),
+            ExecuteMsg::UpdateProperty { token_id, update } => {
+                self.update_property(deps, env, info, token_id, update)
+            }
 
             ExecuteMsg::SetListForShortTermRental {
                 token_id,
@@ -83,10 +225,50 @@ where
             ExecuteMsg::SetUnlistForShorttermRental { token_id } => {
                 self.setunlistforshorttermrental(deps, env, info, token_id)
             }
+            ExecuteMsg::SetStayRules {
+                token_id,
+                maximum_stay,
+                booking_horizon,
+                advance_notice,
+                buffer_days,
+            } => self.set_stay_rules(
+                deps,
+                env,
+                info,
+                token_id,
+                maximum_stay,
+                booking_horizon,
+                advance_notice,
+                buffer_days,
+            ),
+            ExecuteMsg::SetGuestKycLevel {
+                token_id,
+                min_level,
+            } => self.set_guest_kyc_level(deps, env, info, token_id, min_level),
+            ExecuteMsg::SetGuestPricing {
+                token_id,
+                base_guests,
+                extra_guest_fee,
+            } => self.set_guest_pricing(deps, env, info, token_id, base_guests, extra_guest_fee),
+            ExecuteMsg::SetAddOns { token_id, add_ons } => {
+                self.set_add_ons(deps, env, info, token_id, add_ons)
+            }
             ExecuteMsg::SetReservationForShortTerm {
                 token_id,
                 renting_period,
-            } => self.setreservationforshortterm(deps, info, token_id, renting_period),
+                guests,
+                add_ons,
+                voucher,
+            } => self.setreservationforshortterm(
+                deps,
+                env,
+                info,
+                token_id,
+                renting_period,
+                guests,
+                add_ons,
+                voucher,
+            ),
             ExecuteMsg::RejectReservationForShortterm {
                 token_id,
                 traveler,
@@ -131,6 +313,22 @@ where
                 traveler,
                 renting_period,
             } => self.finalizeshorttermrental(deps, env, info, token_id, traveler, renting_period),
+            ExecuteMsg::ReviewStay {
+                token_id,
+                traveler,
+                renting_period,
+                rating,
+                comment_uri,
+            } => self.review_stay(
+                deps,
+                env,
+                info,
+                token_id,
+                traveler,
+                renting_period,
+                rating,
+                comment_uri,
+            ),
 
 
             // ExecuteMsg::SetUnlistForLongtermRental { token_id } => {
@@ -194,6 +392,41 @@ where
             // }
             
             ExecuteMsg::Withdraw { target, amount } => self.withdraw(deps, info, target, amount),
+            ExecuteMsg::UpdateTreasuryConfig {
+                timelock,
+                instant_limits,
+                signers,
+                threshold,
+            } => self.update_treasury_config(
+                deps,
+                info,
+                timelock,
+                instant_limits,
+                signers,
+                threshold,
+            ),
+            ExecuteMsg::ProposeWithdrawal { target, amount } => {
+                self.propose_withdrawal(deps, env, info, target, amount)
+            }
+            ExecuteMsg::ApproveWithdrawal { id } => self.approve_withdrawal(deps, info, id),
+            ExecuteMsg::ExecuteWithdrawal { id } => self.execute_withdrawal(deps, env, info, id),
+            ExecuteMsg::CancelWithdrawal { id } => self.cancel_withdrawal(deps, info, id),
+            ExecuteMsg::SetTokenPayout { token_id, payout } => {
+                self.set_token_payout(deps, env, info, token_id, payout)
+            }
+            ExecuteMsg::SetHostPayout { payout } => self.set_host_payout(deps, info, payout),
+            ExecuteMsg::ClaimPayout { denom } => self.claim_payout(deps, info, denom),
+            ExecuteMsg::GrantRole {
+                token_id,
+                grantee,
+                role,
+                expires,
+            } => self.grant_role(deps, env, info, token_id, grantee, role, expires),
+            ExecuteMsg::RevokeRole {
+                token_id,
+                grantee,
+                role,
+            } => self.revoke_role(deps, env, info, token_id, grantee, role),
 
             ExecuteMsg::SetFeeValue { fee } => self.set_fee_value(deps,info, fee),
             
@@ -228,62 +461,66 @@ where
 // TODO pull this into some sort of trait extension??
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn mint(
         &self,
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         token_id: String,
         owner: String,
         token_uri: Option<String>,
         extension: T,
     ) -> Result<Response<C>, ContractError> {
-        // cw_ownable::assert_owner(deps.storage, &info.sender)?;
-
-        let longterm_rental = LongTermRental {
-            islisted: None,
-            isreserved: None,
-            landlord: None,
-            tenant: None,
-            tenant_address: None,
-            deposit_amount: Uint128::from(0u64),
-            withdrawn_amount: Uint128::from(0u64),
-            renting_flag: None,
-            ejari_flag: None,
-        };
-
-        let shortterm_rental = ShortTermRental {
-            islisted: None,
-            travelers: vec![],
-            price_per_day: 0u64,
-            available_period: vec![],
-            deposit_amount: Uint128::from(0u64),
-            withdrawn_amount: Uint128::from(0u64),
-            denom: "unibi".to_string(),
-            auto_approve: false,
-            cancellation:vec![],
-            minimum_stay:0u64,
-        };
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        if let Some(property) = extension.property() {
+            property.validate()?;
+        }
+        let mut treasury_events = vec![];
+        let is_owner = cw_ownable::is_owner(deps.storage, &info.sender)?;
+        match self.get_mint_policy(deps.storage)? {
+            MintPolicy::OwnerOnly => cw_ownable::assert_owner(deps.storage, &info.sender)?,
+            MintPolicy::Allowlist => {
+                if !is_owner && !self.allowed_minters.has(deps.storage, &info.sender) {
+                    return Err(ContractError::NotMinter {});
+                }
+            }
+            MintPolicy::Open { fee: Some(fee) } if !is_owner => {
+                let sent = info
+                    .funds
+                    .iter()
+                    .find(|coin| coin.denom == fee.denom)
+                    .ok_or(ContractError::InsufficientDeposit {})?;
+                if sent.amount < fee.amount {
+                    return Err(ContractError::InsufficientDeposit {});
+                }
+                treasury_events.push(self.treasury_deposit(deps.storage, sent)?);
+            }
+            MintPolicy::Open { .. } => {}
+        }
 
         // create the token
-        let token = TokenInfo {
-            // owner: deps.api.addr_validate(&owner)?,
-            owner: info.sender.clone(),
-            approvals: vec![],
-            longterm_rental: longterm_rental,
-            shortterm_rental: shortterm_rental,
-            token_uri,
-            extension,
-        };
-        self.tokens
+        let token = TokenInfo::new(owner_addr, token_uri, extension);
+        let token = self
+            .tokens
             .update(deps.storage, &token_id, |old| match old {
                 Some(_) => Err(ContractError::Claimed {}),
                 None => Ok(token),
             })?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("mint")
+                .add_attribute("minter", info.sender.to_string())
+                .add_attribute("owner", token.owner.to_string()),
+        )?;
+        self.record_metadata_version(deps.storage, &env, &token_id, &token, &info.sender)?;
 
         self.increment_tokens(deps.storage)?;
 
@@ -291,203 +528,1904 @@ where
             .add_attribute("action", "mint")
             .add_attribute("minter", info.sender)
             .add_attribute("owner", owner)
-            .add_attribute("token_id", token_id))
+            .add_attribute("token_id", token_id)
+            .add_events(treasury_events))
     }
 
-    pub fn set_fee_value(&self,deps:DepsMut,info: MessageInfo, fee:u64) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
-        self.set_fee(deps.storage, fee)?;
-        Ok(Response::new()
-            .add_attribute("action", "setfee"))
-    }
+    pub fn batch_mint(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        mints: Vec<MintMsg<T>>,
+    ) -> Result<Response<C>, ContractError> {
+        check_batch_size(mints.len())?;
+
+        // an open mint fee is due for every token, anything sent on top goes with the last one
+        let mut fee_funds = None;
+        if let MintPolicy::Open { fee: Some(fee) } = self.get_mint_policy(deps.storage)? {
+            if !cw_ownable::is_owner(deps.storage, &info.sender)? {
+                let sent = info
+                    .funds
+                    .iter()
+                    .find(|coin| coin.denom == fee.denom)
+                    .map(|coin| coin.amount)
+                    .unwrap_or_default();
+                let required = fee
+                    .amount
+                    .checked_mul(Uint128::from(mints.len() as u64))
+                    .map_err(StdError::from)?;
+                if sent < required {
+                    return Err(ContractError::InsufficientDeposit {});
+                }
+                fee_funds = Some((fee, sent - required));
+            }
+        }
 
-    pub fn withdraw(&self, deps: DepsMut, info: MessageInfo, target:String, amount:Coin) -> Result<Response<C>, ContractError> {
-        
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let mut response = Response::new()
+            .add_attribute("action", "batch_mint")
+            .add_attribute("sender", info.sender.clone());
+        let last = mints.len() - 1;
+        for (i, mint) in mints.into_iter().enumerate() {
+            let funds = match &fee_funds {
+                Some((fee, extra)) if i == last => vec![Coin {
+                    denom: fee.denom.clone(),
+                    amount: fee.amount + extra,
+                }],
+                Some((fee, _)) => vec![fee.clone()],
+                None => vec![],
+            };
+            let minted = self.mint(
+                deps.branch(),
+                env.clone(),
+                MessageInfo {
+                    sender: info.sender.clone(),
+                    funds,
+                },
+                mint.token_id.clone(),
+                mint.owner,
+                mint.token_uri,
+                mint.extension,
+            )?;
+            response = response
+                .add_attribute("token_id", mint.token_id)
+                .add_events(minted.events);
+        }
+        Ok(response)
+    }
 
+    pub fn batch_transfer(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_ids: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        check_batch_size(token_ids.len())?;
+
+        let mut response = Response::new()
+            .add_attribute("action", "batch_transfer")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("recipient", recipient.clone());
+        for token_id in token_ids {
+            self._transfer_nft(deps.branch(), &env, &info, &recipient, &token_id, false)?;
+            response = response.add_attribute("token_id", token_id);
+        }
+        Ok(response)
+    }
 
-        if amount.amount.clone() > self.get_balance(deps.storage, amount.denom.clone())? {
-            return Err(ContractError::UnavailableAmount {});
+    pub fn batch_set_listing(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        listings: Vec<ShortTermListingMsg>,
+    ) -> Result<Response<C>, ContractError> {
+        check_batch_size(listings.len())?;
+
+        let mut response = Response::new()
+            .add_attribute("action", "batch_set_listing")
+            .add_attribute("sender", info.sender.clone());
+        for listing in listings {
+            self.setlistforshorttermrental(
+                deps.branch(),
+                env.clone(),
+                info.clone(),
+                listing.token_id.clone(),
+                listing.denom,
+                listing.price_per_day,
+                listing.auto_approve,
+                listing.available_period,
+                listing.minimum_stay,
+                listing.cancellation,
+            )?;
+            response = response.add_attribute("token_id", listing.token_id);
         }
-        
-        Ok(Response::new()
-            .add_attribute("action", "withdraw")
-            .add_message(BankMsg::Send {
-                    to_address: target,
-                    amount: vec![amount],
-                })
-        )
+        Ok(response)
     }
 
-    pub fn update_ownership(
+    pub fn save_listing_template(
+        &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        action: cw_ownable::Action,
+        template_id: String,
+        template: ListingTemplate,
+        propagate: bool,
     ) -> Result<Response<C>, ContractError> {
-        let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
-        Ok(Response::new().add_attributes(ownership.into_attributes()))
-    }
-}
+        self.listing_templates
+            .save(deps.storage, (&info.sender, &template_id), &template)?;
+
+        let mut response = Response::new()
+            .add_attribute("action", "save_listing_template")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("template_id", template_id.clone());
+        if !propagate {
+            return Ok(response);
+        }
 
-impl<'a, T, C, E, Q> Cw721Execute<T, C> for Cw721Contract<'a, T, C, E, Q>
-where
-    T: Serialize + DeserializeOwned + Clone,
-    C: CustomMsg,
-    E: CustomMsg,
-    Q: CustomMsg,
-{
-    type Err = ContractError;
+        let token_ids = self
+            .template_tokens
+            .prefix((&info.sender, &template_id))
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for token_id in token_ids {
+            let mut token = self.tokens.load(deps.storage, &token_id)?;
+            // booked listings keep the terms their guests reserved under, a host whose
+            // pricing role ran out no longer controls the listing, and a token whose
+            // verification was revoked or whose shareholders are paid in another denom
+            // can't be relisted
+            if !token.shortterm_rental.travelers.is_empty()
+                || self.check_verified(deps.storage, &token_id).is_err()
+                || self
+                    .check_rent_denom(deps.storage, &token_id, &template.denom)
+                    .is_err()
+                || self
+                    .check_has_role(
+                        deps.as_ref(),
+                        &env,
+                        &info,
+                        &token_id,
+                        &token,
+                        TokenRole::PricingManager,
+                    )
+                    .is_err()
+            {
+                response = response.add_attribute("skipped", token_id);
+                continue;
+            }
+            self.apply_template(deps.storage, &env, &token_id, &mut token, &template)?;
+            response = response.add_attribute("token_id", token_id);
+        }
+        Ok(response)
+    }
 
-    fn transfer_nft(
+    pub fn remove_listing_template(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        recipient: String,
-        token_id: String,
+        template_id: String,
     ) -> Result<Response<C>, ContractError> {
-        self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+        self.listing_templates
+            .load(deps.storage, (&info.sender, &template_id))?;
+        self.listing_templates
+            .remove(deps.storage, (&info.sender, &template_id));
+        let token_ids = self
+            .template_tokens
+            .prefix((&info.sender, &template_id))
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for token_id in token_ids {
+            self.unlink_template(deps.storage, &token_id)?;
+        }
 
         Ok(Response::new()
-            .add_attribute("action", "transfer_nft")
+            .add_attribute("action", "remove_listing_template")
             .add_attribute("sender", info.sender)
-            .add_attribute("recipient", recipient)
-            .add_attribute("token_id", token_id))
+            .add_attribute("template_id", template_id))
     }
 
-    fn send_nft(
+    pub fn apply_listing_template(
         &self,
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        contract: String,
-        token_id: String,
-        msg: Binary,
+        template_id: String,
+        token_ids: Vec<String>,
     ) -> Result<Response<C>, ContractError> {
-        // Transfer token
-        self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
+        check_batch_size(token_ids.len())?;
+        let template = self
+            .listing_templates
+            .load(deps.storage, (&info.sender, &template_id))?;
+
+        let mut response = Response::new()
+            .add_attribute("action", "apply_listing_template")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("template_id", template_id.clone());
+        for token_id in token_ids {
+            let available_period = self
+                .tokens
+                .load(deps.storage, &token_id)?
+                .shortterm_rental
+                .available_period;
+            let template = template.clone();
+            self.setlistforshorttermrental(
+                deps.branch(),
+                env.clone(),
+                info.clone(),
+                token_id.clone(),
+                template.denom,
+                template.price_per_day,
+                template.auto_approve,
+                available_period,
+                template.minimum_stay,
+                template.cancellation,
+            )?;
+            self.template_links.save(
+                deps.storage,
+                &token_id,
+                &(info.sender.clone(), template_id.clone()),
+            )?;
+            self.template_tokens.save(
+                deps.storage,
+                (&info.sender, &template_id, &token_id),
+                &Empty {},
+            )?;
+            response = response.add_attribute("token_id", token_id);
+        }
+        Ok(response)
+    }
 
-        let send = Cw721ReceiveMsg {
-            sender: info.sender.to_string(),
-            token_id: token_id.clone(),
-            msg,
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_voucher(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        code: String,
+        discount: VoucherDiscount,
+        max_uses: Option<u32>,
+        expires: Option<Expiration>,
+        token_ids: Vec<String>,
+        hosts: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        if code.trim().is_empty() || code.len() > 64 {
+            return Err(ContractError::InvalidInput {});
+        }
+        match &discount {
+            VoucherDiscount::Percentage { percent } if *percent == 0 || *percent > 100 => {
+                return Err(ContractError::InvalidInput {});
+            }
+            VoucherDiscount::Fixed { amount, .. } if amount.is_zero() => {
+                return Err(ContractError::InvalidInput {});
+            }
+            _ => {}
+        }
+        if self.vouchers.has(deps.storage, (&info.sender, &code)) {
+            return Err(ContractError::VoucherExists {});
+        }
+        let hosts = hosts
+            .iter()
+            .map(|host| deps.api.addr_validate(host))
+            .collect::<StdResult<Vec<_>>>()?;
+        // a host's codes only ever apply to the host's own tokens
+        let hosts = if cw_ownable::is_owner(deps.storage, &info.sender)? {
+            hosts
+        } else if hosts.iter().all(|host| *host == info.sender) {
+            vec![info.sender.clone()]
+        } else {
+            return Err(ContractError::Ownership(OwnershipError::NotOwner));
         };
 
-        // Send message
+        self.vouchers.save(
+            deps.storage,
+            (&info.sender, &code),
+            &Voucher {
+                issuer: info.sender.clone(),
+                discount,
+                max_uses,
+                uses: 0,
+                expires: expires.unwrap_or_default(),
+                token_ids,
+                hosts,
+            },
+        )?;
+
         Ok(Response::new()
-            .add_message(send.into_cosmos_msg(contract.clone())?)
-            .add_attribute("action", "send_nft")
+            .add_attribute("action", "create_voucher")
             .add_attribute("sender", info.sender)
-            .add_attribute("recipient", contract)
-            .add_attribute("token_id", token_id))
+            .add_attribute("code", code))
     }
 
-    fn approve(
+    pub fn remove_voucher(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        spender: String,
-        token_id: String,
-        expires: Option<Expiration>,
+        code: String,
+        issuer: Option<String>,
     ) -> Result<Response<C>, ContractError> {
-        self._update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+        let issuer = match issuer {
+            Some(issuer) => deps.api.addr_validate(&issuer)?,
+            None => info.sender.clone(),
+        };
+        if issuer != info.sender && !cw_ownable::is_owner(deps.storage, &info.sender)? {
+            return Err(ContractError::Ownership(OwnershipError::NotOwner));
+        }
+        self.vouchers.load(deps.storage, (&issuer, &code))?;
+        self.vouchers.remove(deps.storage, (&issuer, &code));
 
         Ok(Response::new()
-            .add_attribute("action", "approve")
+            .add_attribute("action", "remove_voucher")
             .add_attribute("sender", info.sender)
-            .add_attribute("spender", spender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("code", code))
     }
 
-    fn revoke(
+    pub fn update_mint_policy(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        spender: String,
-        token_id: String,
+        policy: MintPolicy,
     ) -> Result<Response<C>, ContractError> {
-        self._update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.mint_policy.save(deps.storage, &policy)?;
 
         Ok(Response::new()
-            .add_attribute("action", "revoke")
-            .add_attribute("sender", info.sender)
-            .add_attribute("spender", spender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("action", "update_mint_policy")
+            .add_attribute("sender", info.sender))
     }
 
-    fn approve_all(
+    pub fn update_minters(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        operator: String,
-        expires: Option<Expiration>,
+        add: Vec<String>,
+        remove: Vec<String>,
     ) -> Result<Response<C>, ContractError> {
-        // reject expired data as invalid
-        let expires = expires.unwrap_or_default();
-        if expires.is_expired(&env.block) {
-            return Err(ContractError::Expired {});
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        for minter in add {
+            let minter = deps.api.addr_validate(&minter)?;
+            self.allowed_minters
+                .save(deps.storage, &minter, &Empty {})?;
+        }
+        for minter in remove {
+            let minter = deps.api.addr_validate(&minter)?;
+            self.allowed_minters.remove(deps.storage, &minter);
         }
-
-        // set the operator for us
-        let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
 
         Ok(Response::new()
-            .add_attribute("action", "approve_all")
-            .add_attribute("sender", info.sender)
-            .add_attribute("operator", operator))
+            .add_attribute("action", "update_minters")
+            .add_attribute("sender", info.sender))
     }
 
-    fn revoke_all(
+    pub fn transfer_nft_with_reservations(
         &self,
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
-        operator: String,
+        recipient: String,
+        token_id: String,
     ) -> Result<Response<C>, ContractError> {
-        let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .remove(deps.storage, (&info.sender, &operator_addr));
+        let (_, settlement) = self._transfer_nft(deps, &env, &info, &recipient, &token_id, true)?;
 
         Ok(Response::new()
-            .add_attribute("action", "revoke_all")
+            .add_attribute("action", "transfer_nft_with_reservations")
             .add_attribute("sender", info.sender)
-            .add_attribute("operator", operator))
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id)
+            .add_events(settlement.events)
+            .add_messages(settlement.messages))
     }
 
-    fn burn(
+    pub fn send_nft_with_reservations(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
+        contract: String,
         token_id: String,
+        msg: Binary,
     ) -> Result<Response<C>, ContractError> {
-        let token = self.tokens.load(deps.storage, &token_id)?;
-        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        let (_, settlement) = self._transfer_nft(deps, &env, &info, &contract, &token_id, true)?;
 
-        self.tokens.remove(deps.storage, &token_id)?;
-        self.decrement_tokens(deps.storage)?;
+        let send = Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id: token_id.clone(),
+            msg,
+        };
 
         Ok(Response::new()
-            .add_attribute("action", "burn")
+            .add_messages(settlement.messages)
+            .add_message(send.into_cosmos_msg(contract.clone())?)
+            .add_attribute("action", "send_nft_with_reservations")
             .add_attribute("sender", info.sender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id)
+            .add_events(settlement.events))
     }
-}
+
+    pub fn emergency_burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        // shareholders would be left with shares of a token that no longer exists
+        if self.is_fractionalized(deps.storage, &token_id)? {
+            return Err(ContractError::Fractionalized {});
+        }
+
+        // the host keeps what bookings earned so far, forfeits of cancelled ones included,
+        // and travelers get back the rest of their deposits
+        let settlement = self.settle_earned_rent(deps.storage, &env, &token_id, &mut token)?;
+        let mut refunds = settlement.messages;
+        if let Some(auction) = self.auctions.may_load(deps.storage, &token_id)? {
+            self.auctions.remove(deps.storage, &token_id);
+            refunds.extend(auction.refund_highest_bid());
+        }
+        for offer in self.token_offers(deps.storage, &token_id)? {
+            self.offers.remove(deps.storage, (&token_id, &offer.buyer));
+            refunds.push(BankMsg::Send {
+                to_address: offer.buyer.into_string(),
+                amount: vec![offer.amount],
+            });
+        }
+        for traveler in token.shortterm_rental.travelers.iter() {
+            if let Some(address) = &traveler.address {
+                if !traveler.deposit_amount.is_zero() {
+                    refunds.push(BankMsg::Send {
+                        to_address: address.to_string(),
+                        amount: vec![Coin {
+                            denom: token.shortterm_rental.denom.clone(),
+                            amount: traveler.deposit_amount,
+                        }],
+                    });
+                }
+            }
+        }
+        let longterm = &token.longterm_rental;
+        if let (Some(address), Some(tenant)) = (&longterm.tenant_address, &longterm.tenant) {
+            let remaining = longterm
+                .deposit_amount
+                .saturating_sub(longterm.withdrawn_amount);
+            if !remaining.is_zero() {
+                refunds.push(BankMsg::Send {
+                    to_address: address.to_string(),
+                    amount: vec![Coin {
+                        denom: tenant.deposit_denom.clone(),
+                        amount: remaining,
+                    }],
+                });
+            }
+        }
+
+        self.remove_token(deps.storage, &token_id)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("burn")
+                .add_attribute("owner", token.owner.to_string())
+                .add_attribute("emergency", "true"),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "emergency_burn")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_messages(refunds)
+            .add_events(settlement.events))
+    }
+
+    pub fn list_for_sale(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        price: Coin,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        // a sale could never be settled, fractionalized tokens can't be transferred
+        if self.is_fractionalized(deps.storage, &token_id)? {
+            return Err(ContractError::Fractionalized {});
+        }
+        if price.amount.is_zero() {
+            return Err(ContractError::InvalidInput {});
+        }
+
+        let listing = SaleListing {
+            seller: token.owner,
+            price,
+        };
+        self.sales.save(deps.storage, &token_id, &listing)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("listing")
+                .add_attribute("market", "sale")
+                .add_attribute("listed", "true")
+                .add_attribute("price", listing.price.to_string()),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "list_for_sale")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", listing.price.to_string()))
+    }
+
+    pub fn delist_from_sale(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        self.sales.remove(deps.storage, &token_id);
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("listing")
+                .add_attribute("market", "sale")
+                .add_attribute("listed", "false"),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "delist_from_sale")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn buy(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let listing = self
+            .sales
+            .may_load(deps.storage, &token_id)?
+            .ok_or(ContractError::NotForSale {})?;
+        if info.funds != vec![listing.price.clone()] {
+            return Err(ContractError::InvalidDeposit {});
+        }
+
+        let settlement = self._sell(deps, &env, &token_id, &info.sender, listing.price.clone())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "buy")
+            .add_attribute("sender", info.sender)
+            .add_attribute("seller", listing.seller)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", listing.price.to_string())
+            .add_events(settlement.events)
+            .add_messages(settlement.messages))
+    }
+
+    pub fn make_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        if token.owner == info.sender {
+            return Err(ContractError::InvalidInput {});
+        }
+        if info.funds.len() != 1 || info.funds[0].amount.is_zero() {
+            return Err(ContractError::InvalidDeposit {});
+        }
+        // reject expired data as invalid
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+
+        // a new offer replaces the previous one, whose funds go back
+        let mut refunds = vec![];
+        if let Some(previous) = self
+            .offers
+            .may_load(deps.storage, (&token_id, &info.sender))?
+        {
+            refunds.push(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![previous.amount],
+            });
+        }
+        let offer = Offer {
+            buyer: info.sender.clone(),
+            amount: info.funds[0].clone(),
+            expires,
+        };
+        self.offers
+            .save(deps.storage, (&token_id, &info.sender), &offer)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "make_offer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("amount", offer.amount.to_string())
+            .add_messages(refunds))
+    }
+
+    pub fn accept_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        buyer: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+
+        let buyer = deps.api.addr_validate(&buyer)?;
+        let offer = self
+            .offers
+            .may_load(deps.storage, (&token_id, &buyer))?
+            .ok_or(ContractError::OfferNotFound {})?;
+        if offer.expires.is_expired(&env.block) {
+            return Err(ContractError::OfferExpired {});
+        }
+        self.offers.remove(deps.storage, (&token_id, &buyer));
+
+        let settlement = self._sell(deps, &env, &token_id, &buyer, offer.amount.clone())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_offer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("buyer", buyer)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", offer.amount.to_string())
+            .add_events(settlement.events)
+            .add_messages(settlement.messages))
+    }
+
+    pub fn cancel_offer(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let offer = self
+            .offers
+            .may_load(deps.storage, (&token_id, &info.sender))?
+            .ok_or(ContractError::OfferNotFound {})?;
+        self.offers.remove(deps.storage, (&token_id, &info.sender));
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_offer")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("token_id", token_id)
+            .add_message(BankMsg::Send {
+                to_address: info.sender.into_string(),
+                amount: vec![offer.amount],
+            }))
+    }
+
+    pub fn fractionalize(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        shares: Uint128,
+        buyout_price: Coin,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        // shares of an earlier buyout have to be redeemed first
+        if self.fractions.has(deps.storage, &token_id) {
+            return Err(ContractError::Fractionalized {});
+        }
+        if self.auctions.has(deps.storage, &token_id) {
+            return Err(ContractError::AuctionActive {});
+        }
+        if self.sales.has(deps.storage, &token_id) {
+            return Err(ContractError::ListedForSale {});
+        }
+        if shares.is_zero() || buyout_price.amount.is_zero() {
+            return Err(ContractError::InvalidInput {});
+        }
+        // only rent in the buyout denom can be shared out
+        let denom = &token.shortterm_rental.denom;
+        if !denom.is_empty() && *denom != buyout_price.denom {
+            return Err(ContractError::DenomMismatch {
+                denom: buyout_price.denom,
+            });
+        }
+
+        let fraction = Fraction {
+            curator: token.owner.clone(),
+            total_shares: shares,
+            outstanding_shares: shares,
+            buyout_price,
+            dividend_index: Decimal::zero(),
+            undistributed: Uint128::zero(),
+            bought_out_by: None,
+        };
+        self.fractions.save(deps.storage, &token_id, &fraction)?;
+        self.shareholders.save(
+            deps.storage,
+            (&token_id, &token.owner),
+            &Shareholder {
+                shares,
+                ..Shareholder::default()
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "fractionalize")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("shares", shares))
+    }
+
+    pub fn transfer_shares(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        recipient: String,
+        amount: Uint128,
+    ) -> Result<Response<C>, ContractError> {
+        let recipient_addr = deps.api.addr_validate(&recipient)?;
+        if amount.is_zero() || recipient_addr == info.sender {
+            return Err(ContractError::InvalidInput {});
+        }
+        let mut sender = self.shareholder(deps.storage, &token_id, &info.sender)?;
+        let mut receiver = self.shareholder(deps.storage, &token_id, &recipient_addr)?;
+        sender.shares = sender
+            .shares
+            .checked_sub(amount)
+            .map_err(|_| ContractError::InsufficientShares {})?;
+        receiver.shares += amount;
+        self.shareholders
+            .save(deps.storage, (&token_id, &info.sender), &sender)?;
+        self.shareholders
+            .save(deps.storage, (&token_id, &recipient_addr), &receiver)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_shares")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id)
+            .add_attribute("amount", amount))
+    }
+
+    pub fn claim_dividends(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let fraction = self.fractions.load(deps.storage, &token_id)?;
+        let mut shareholder = self.shareholder(deps.storage, &token_id, &info.sender)?;
+        if shareholder.pending.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+        let amount = Coin {
+            denom: fraction.buyout_price.denom,
+            amount: shareholder.pending,
+        };
+        shareholder.pending = Uint128::zero();
+        self.shareholders
+            .save(deps.storage, (&token_id, &info.sender), &shareholder)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "claim_dividends")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("token_id", token_id)
+            .add_attribute("amount", amount.to_string())
+            .add_message(BankMsg::Send {
+                to_address: info.sender.into_string(),
+                amount: vec![amount],
+            }))
+    }
+
+    pub fn buyout(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let fraction = self.fractions.load(deps.storage, &token_id)?;
+        if fraction.bought_out_by.is_some() {
+            return Err(ContractError::BoughtOut {});
+        }
+        if info.funds != vec![fraction.buyout_price] {
+            return Err(ContractError::InvalidDeposit {});
+        }
+
+        // rent earned up to now still belongs to the shareholders
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        let mut settlement = Settlement::default();
+        if !token.shortterm_rental.travelers.is_empty() {
+            settlement = self.settle_earned_rent(deps.storage, &env, &token_id, &mut token)?;
+            self.tokens.save(deps.storage, &token_id, &token)?;
+        }
+        self.fractions
+            .update(deps.storage, &token_id, |fraction| -> StdResult<_> {
+                let mut fraction = fraction.ok_or_else(|| StdError::not_found("Fraction"))?;
+                fraction.bought_out_by = Some(info.sender.clone());
+                Ok(fraction)
+            })?;
+
+        let owner_info = MessageInfo {
+            sender: token.owner,
+            funds: vec![],
+        };
+        let (_, carried) = self._transfer_nft(
+            deps.branch(),
+            &env,
+            &owner_info,
+            info.sender.as_str(),
+            &token_id,
+            true,
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "buyout")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_events(settlement.events)
+            .add_events(carried.events)
+            .add_messages(settlement.messages)
+            .add_messages(carried.messages))
+    }
+
+    pub fn redeem_shares(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let mut fraction = self.fractions.load(deps.storage, &token_id)?;
+        if fraction.bought_out_by.is_none() {
+            return Err(ContractError::NotBoughtOut {});
+        }
+        let shareholder = self.shareholder(deps.storage, &token_id, &info.sender)?;
+        if shareholder.shares.is_zero() {
+            return Err(ContractError::InsufficientShares {});
+        }
+        let amount = fraction
+            .buyout_price
+            .amount
+            .multiply_ratio(shareholder.shares, fraction.total_shares)
+            + shareholder.pending;
+        self.shareholders
+            .remove(deps.storage, (&token_id, &info.sender));
+        fraction.outstanding_shares -= shareholder.shares;
+        if fraction.outstanding_shares.is_zero() {
+            self.fractions.remove(deps.storage, &token_id);
+        } else {
+            self.fractions.save(deps.storage, &token_id, &fraction)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "redeem_shares")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("token_id", token_id)
+            .add_attribute("shares", shareholder.shares)
+            .add_message(BankMsg::Send {
+                to_address: info.sender.into_string(),
+                amount: vec![Coin {
+                    denom: fraction.buyout_price.denom,
+                    amount,
+                }],
+            }))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_auction(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        kind: AuctionKind,
+        denom: String,
+        price: u64,
+        duration: u64,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        if self.auctions.has(deps.storage, &token_id) {
+            return Err(ContractError::AuctionActive {});
+        }
+        // the winning bid could never be settled, fractionalized tokens can't be transferred
+        if self.is_fractionalized(deps.storage, &token_id)? {
+            return Err(ContractError::Fractionalized {});
+        }
+        if duration == 0 {
+            return Err(ContractError::InvalidInput {});
+        }
+        if let AuctionKind::Dutch { floor_price } = kind {
+            if floor_price > price {
+                return Err(ContractError::InvalidInput {});
+            }
+        }
+
+        let start_time = env.block.time.seconds();
+        let end_time = start_time
+            .checked_add(duration)
+            .ok_or(ContractError::InvalidInput {})?;
+        let auction = Auction {
+            seller: token.owner,
+            kind,
+            denom,
+            start_price: price,
+            start_time,
+            end_time,
+            bids: vec![],
+        };
+        self.auctions.save(deps.storage, &token_id, &auction)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "start_auction")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("end_time", auction.end_time.to_string()))
+    }
+
+    pub fn place_bid(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let mut auction = self.auctions.load(deps.storage, &token_id)?;
+        let current_time = env.block.time.seconds();
+        if current_time >= auction.end_time {
+            return Err(ContractError::AuctionEnded {});
+        }
+        if info.sender == auction.seller {
+            return Err(ContractError::InvalidInput {});
+        }
+        if info.funds.len() != 1 || info.funds[0].denom != auction.denom {
+            return Err(ContractError::InvalidDeposit {});
+        }
+        let offer = u64::try_from(info.funds[0].amount.u128())
+            .map_err(|_| ContractError::InvalidDeposit {})?;
+        let min_bid = auction.min_bid(current_time);
+        if offer < min_bid {
+            return Err(ContractError::BidTooLow { min_bid });
+        }
+
+        let response = Response::new()
+            .add_attribute("action", "place_bid")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("token_id", token_id.clone())
+            .add_attribute("offer", offer.to_string());
+
+        match auction.kind {
+            AuctionKind::English { extension, .. } => {
+                let refund = auction.refund_highest_bid();
+                auction.bids.push(Bid {
+                    buyer: info.sender.into_string(),
+                    offer,
+                });
+                // bids close to the end give others time to respond
+                if auction.end_time - current_time < extension {
+                    auction.end_time = current_time.saturating_add(extension);
+                }
+                self.auctions.save(deps.storage, &token_id, &auction)?;
+
+                Ok(response
+                    .add_attribute("end_time", auction.end_time.to_string())
+                    .add_messages(refund))
+            }
+            AuctionKind::Dutch { .. } => {
+                // the first bid at the current price wins, anything above it goes back
+                self.auctions.remove(deps.storage, &token_id);
+                let price = Coin {
+                    denom: auction.denom.clone(),
+                    amount: Uint128::from(min_bid),
+                };
+                let settlement = self._sell(deps, &env, &token_id, &info.sender, price)?;
+                let mut refund = vec![];
+                if offer > min_bid {
+                    refund.push(BankMsg::Send {
+                        to_address: info.sender.to_string(),
+                        amount: vec![Coin {
+                            denom: auction.denom,
+                            amount: Uint128::from(offer - min_bid),
+                        }],
+                    });
+                }
+
+                Ok(response
+                    .add_events(settlement.events)
+                    .add_messages(settlement.messages)
+                    .add_messages(refund))
+            }
+        }
+    }
+
+    pub fn settle_auction(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let auction = self.auctions.load(deps.storage, &token_id)?;
+        if env.block.time.seconds() < auction.end_time {
+            return Err(ContractError::AuctionNotEnded {});
+        }
+        self.auctions.remove(deps.storage, &token_id);
+
+        let response = Response::new()
+            .add_attribute("action", "settle_auction")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id.clone());
+
+        let winner = match (&auction.kind, auction.bids.last()) {
+            (AuctionKind::English { reserve_price, .. }, Some(bid))
+                if bid.offer >= *reserve_price =>
+            {
+                Some(bid.clone())
+            }
+            _ => None,
+        };
+        match winner {
+            Some(bid) => {
+                let buyer = deps.api.addr_validate(&bid.buyer)?;
+                let price = Coin {
+                    denom: auction.denom,
+                    amount: Uint128::from(bid.offer),
+                };
+                let settlement = self._sell(deps, &env, &token_id, &buyer, price)?;
+
+                Ok(response
+                    .add_attribute("winner", bid.buyer)
+                    .add_events(settlement.events)
+                    .add_messages(settlement.messages))
+            }
+            // reserve not met, the highest bidder gets its funds back
+            None => Ok(response.add_messages(auction.refund_highest_bid())),
+        }
+    }
+
+    pub fn cancel_auction(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        let auction = self.auctions.load(deps.storage, &token_id)?;
+        if !auction.bids.is_empty() {
+            return Err(ContractError::AuctionHasBids {});
+        }
+        self.auctions.remove(deps.storage, &token_id);
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_auction")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn set_default_royalty(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        royalty: Option<RoyaltyMsg>,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        match royalty {
+            Some(royalty) => {
+                let royalty = validate_royalty(deps.api, royalty)?;
+                self.default_royalty.save(deps.storage, &royalty)?;
+            }
+            None => self.default_royalty.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_default_royalty")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn set_token_royalty(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        royalty: Option<RoyaltyMsg>,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // make sure the token exists
+        self.tokens.load(deps.storage, &token_id)?;
+        match royalty {
+            Some(royalty) => {
+                let royalty = validate_royalty(deps.api, royalty)?;
+                self.royalties.save(deps.storage, &token_id, &royalty)?;
+            }
+            None => self.royalties.remove(deps.storage, &token_id),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_token_royalty")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn update_verifiers(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        for verifier in add {
+            let verifier = deps.api.addr_validate(&verifier)?;
+            self.verifiers.save(deps.storage, &verifier, &Empty {})?;
+        }
+        for verifier in remove {
+            let verifier = deps.api.addr_validate(&verifier)?;
+            self.verifiers.remove(deps.storage, &verifier);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_verifiers")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn set_require_verification(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        required: bool,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.require_verification.save(deps.storage, &required)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_require_verification")
+            .add_attribute("sender", info.sender)
+            .add_attribute("required", required.to_string()))
+    }
+
+    pub fn verify_property(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        attestation_uri: String,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.verifiers.has(deps.storage, &info.sender) {
+            return Err(ContractError::NotVerifier {});
+        }
+        if attestation_uri.trim().is_empty() {
+            return Err(ContractError::InvalidInput {});
+        }
+        // make sure the token exists
+        self.tokens.load(deps.storage, &token_id)?;
+
+        let verification = Verification {
+            verifier: info.sender.clone(),
+            attestation_uri,
+            verified_at: env.block.time.seconds(),
+        };
+        self.verifications
+            .save(deps.storage, &token_id, &verification)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "verify_property")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("attestation_uri", verification.attestation_uri))
+    }
+
+    pub fn revoke_verification(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.verifiers.has(deps.storage, &info.sender)
+            && !cw_ownable::is_owner(deps.storage, &info.sender)?
+        {
+            return Err(ContractError::NotVerifier {});
+        }
+        self.verifications.remove(deps.storage, &token_id);
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_verification")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn update_kyc_providers(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        for provider in add {
+            let provider = deps.api.addr_validate(&provider)?;
+            self.kyc_providers
+                .save(deps.storage, &provider, &Empty {})?;
+        }
+        for provider in remove {
+            let provider = deps.api.addr_validate(&provider)?;
+            self.kyc_providers.remove(deps.storage, &provider);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_kyc_providers")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn attest_guest(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        address: String,
+        level: u8,
+        expires: Expiration,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.kyc_providers.has(deps.storage, &info.sender) {
+            return Err(ContractError::NotKycProvider {});
+        }
+        if level == 0 || expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidInput {});
+        }
+        let address = deps.api.addr_validate(&address)?;
+
+        let attestation = GuestAttestation {
+            provider: info.sender.clone(),
+            level,
+            expires,
+            attested_at: env.block.time.seconds(),
+        };
+        self.attestations
+            .save(deps.storage, &address, &attestation)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "attest_guest")
+            .add_attribute("sender", info.sender)
+            .add_attribute("address", address)
+            .add_attribute("level", level.to_string()))
+    }
+
+    pub fn revoke_attestation(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.kyc_providers.has(deps.storage, &info.sender)
+            && !cw_ownable::is_owner(deps.storage, &info.sender)?
+        {
+            return Err(ContractError::NotKycProvider {});
+        }
+        let address = deps.api.addr_validate(&address)?;
+        self.attestations.remove(deps.storage, &address);
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_attestation")
+            .add_attribute("sender", info.sender)
+            .add_attribute("address", address))
+    }
+
+    pub fn update_global_blocklist(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        for address in add {
+            let address = deps.api.addr_validate(&address)?;
+            self.global_blocklist
+                .save(deps.storage, &address, &Empty {})?;
+        }
+        for address in remove {
+            let address = deps.api.addr_validate(&address)?;
+            self.global_blocklist.remove(deps.storage, &address);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_global_blocklist")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn update_host_blocklist(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        for address in add {
+            let address = deps.api.addr_validate(&address)?;
+            self.host_blocklists
+                .save(deps.storage, (&info.sender, &address), &Empty {})?;
+        }
+        for address in remove {
+            let address = deps.api.addr_validate(&address)?;
+            self.host_blocklists
+                .remove(deps.storage, (&info.sender, &address));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_host_blocklist")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn set_fee_value(&self,deps:DepsMut,info: MessageInfo, fee:u64) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // in basis points, more would take more than the whole price
+        if fee > 10000 {
+            return Err(ContractError::InvalidInput {});
+        }
+        self.set_fee(deps.storage, fee)?;
+        Ok(Response::new()
+            .add_attribute("action", "setfee"))
+    }
+
+    pub fn withdraw(&self, deps: DepsMut, info: MessageInfo, target:String, amount:Coin) -> Result<Response<C>, ContractError> {
+        
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+
+        if amount.amount.clone() > self.get_balance(deps.storage, amount.denom.clone())? {
+            return Err(ContractError::UnavailableAmount {});
+        }
+
+        // once treasury controls are configured, only small amounts skip the proposal flow
+        if let Some(config) = self.treasury_config.may_load(deps.storage)? {
+            if amount.amount > config.instant_limit(&amount.denom) {
+                return Err(ContractError::ProposalRequired {});
+            }
+        }
+
+        let event = self.treasury_withdraw(deps.storage, &amount)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw")
+            .add_event(event)
+            .add_message(BankMsg::Send {
+                    to_address: target,
+                    amount: vec![amount],
+                })
+        )
+    }
+
+    pub fn update_treasury_config(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        timelock: u64,
+        instant_limits: Vec<Coin>,
+        signers: Vec<String>,
+        threshold: u32,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut signer_addrs: Vec<Addr> = vec![];
+        for signer in signers {
+            let addr = deps.api.addr_validate(&signer)?;
+            if !signer_addrs.contains(&addr) {
+                signer_addrs.push(addr);
+            }
+        }
+        // with signers at least one of them has to approve, and enough of them must exist
+        if threshold as usize > signer_addrs.len() || (!signer_addrs.is_empty() && threshold == 0) {
+            return Err(ContractError::InvalidInput {});
+        }
+
+        let config = TreasuryConfig {
+            timelock,
+            instant_limits,
+            signers: signer_addrs,
+            threshold,
+        };
+        self.treasury_config.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_treasury_config")
+            .add_attribute("sender", info.sender)
+            .add_attribute("timelock", timelock.to_string())
+            .add_attribute("threshold", threshold.to_string()))
+    }
+
+    pub fn propose_withdrawal(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        target: String,
+        amount: Coin,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        if amount.amount > self.get_balance(deps.storage, amount.denom.clone())? {
+            return Err(ContractError::UnavailableAmount {});
+        }
+
+        let config = self
+            .treasury_config
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        let executable_at = env
+            .block
+            .time
+            .seconds()
+            .checked_add(config.timelock)
+            .ok_or(ContractError::InvalidInput {})?;
+        let id = self.next_withdrawal_id(deps.storage)?;
+        let proposal = WithdrawalProposal {
+            id,
+            proposer: info.sender.clone(),
+            target: deps.api.addr_validate(&target)?,
+            amount,
+            approvals: vec![],
+            executable_at,
+            status: WithdrawalStatus::Pending,
+        };
+        self.withdrawals.save(deps.storage, id, &proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose_withdrawal")
+            .add_attribute("sender", info.sender)
+            .add_attribute("withdrawal_id", id.to_string())
+            .add_attribute("executable_at", proposal.executable_at.to_string()))
+    }
+
+    pub fn approve_withdrawal(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response<C>, ContractError> {
+        let config = self
+            .treasury_config
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if !config.is_signer(&info.sender) {
+            return Err(ContractError::NotSigner {});
+        }
+
+        let mut proposal = self.withdrawals.load(deps.storage, id)?;
+        if proposal.status != WithdrawalStatus::Pending {
+            return Err(ContractError::WithdrawalNotPending {});
+        }
+        if proposal.approvals.contains(&info.sender) {
+            return Err(ContractError::ApprovedAlready {});
+        }
+        proposal.approvals.push(info.sender.clone());
+        self.withdrawals.save(deps.storage, id, &proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "approve_withdrawal")
+            .add_attribute("sender", info.sender)
+            .add_attribute("withdrawal_id", id.to_string()))
+    }
+
+    pub fn execute_withdrawal(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response<C>, ContractError> {
+        let config = self
+            .treasury_config
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if !config.is_signer(&info.sender) {
+            cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        }
+
+        let mut proposal = self.withdrawals.load(deps.storage, id)?;
+        if proposal.status != WithdrawalStatus::Pending {
+            return Err(ContractError::WithdrawalNotPending {});
+        }
+        if env.block.time.seconds() < proposal.executable_at {
+            return Err(ContractError::Timelocked {
+                executable_at: proposal.executable_at,
+            });
+        }
+
+        // only approvals from the current signer set count towards the threshold
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|signer| config.is_signer(signer))
+            .count() as u32;
+        if approvals < config.threshold {
+            return Err(ContractError::InsufficientApprovals {
+                approvals,
+                threshold: config.threshold,
+            });
+        }
+
+        let event = self.treasury_withdraw(deps.storage, &proposal.amount)?;
+
+        proposal.status = WithdrawalStatus::Executed;
+        self.withdrawals.save(deps.storage, id, &proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "execute_withdrawal")
+            .add_attribute("sender", info.sender)
+            .add_attribute("withdrawal_id", id.to_string())
+            .add_event(event)
+            .add_message(BankMsg::Send {
+                to_address: proposal.target.into_string(),
+                amount: vec![proposal.amount],
+            }))
+    }
+
+    pub fn cancel_withdrawal(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut proposal = self.withdrawals.load(deps.storage, id)?;
+        if proposal.status != WithdrawalStatus::Pending {
+            return Err(ContractError::WithdrawalNotPending {});
+        }
+        proposal.status = WithdrawalStatus::Cancelled;
+        self.withdrawals.save(deps.storage, id, &proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_withdrawal")
+            .add_attribute("sender", info.sender)
+            .add_attribute("withdrawal_id", id.to_string()))
+    }
+
+    pub fn set_token_payout(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        payout: Option<PayoutMsg>,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+
+        match payout {
+            Some(payout) => {
+                let config = validate_payout(deps.api, payout)?;
+                self.token_payouts.save(deps.storage, &token_id, &config)?;
+            }
+            None => self.token_payouts.remove(deps.storage, &token_id),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_token_payout")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn set_host_payout(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        payout: Option<PayoutMsg>,
+    ) -> Result<Response<C>, ContractError> {
+        match payout {
+            Some(payout) => {
+                let config = validate_payout(deps.api, payout)?;
+                self.host_payouts
+                    .save(deps.storage, &info.sender, &config)?;
+            }
+            None => self.host_payouts.remove(deps.storage, &info.sender),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_host_payout")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn claim_payout(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        denom: String,
+    ) -> Result<Response<C>, ContractError> {
+        let amount = self
+            .claimable
+            .may_load(deps.storage, (&info.sender, &denom))?
+            .unwrap_or_default();
+        if amount.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+        self.claimable.remove(deps.storage, (&info.sender, &denom));
+
+        Ok(Response::new()
+            .add_attribute("action", "claim_payout")
+            .add_attribute("sender", info.sender.clone())
+            .add_attribute("amount", amount)
+            .add_message(BankMsg::Send {
+                to_address: info.sender.into_string(),
+                amount: vec![Coin { denom, amount }],
+            }))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn grant_role(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        grantee: String,
+        role: TokenRole,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+
+        // reject expired data as invalid
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+
+        let grantee_addr = deps.api.addr_validate(&grantee)?;
+        let mut grants = self
+            .roles
+            .may_load(deps.storage, (&token_id, &grantee_addr))?
+            .unwrap_or_default();
+        grants.retain(|grant| grant.role != role);
+        grants.push(RoleGrant { role, expires });
+        self.roles
+            .save(deps.storage, (&token_id, &grantee_addr), &grants)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "grant_role")
+            .add_attribute("sender", info.sender)
+            .add_attribute("grantee", grantee)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn revoke_role(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        grantee: String,
+        role: TokenRole,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+
+        let grantee_addr = deps.api.addr_validate(&grantee)?;
+        let mut grants = self
+            .roles
+            .may_load(deps.storage, (&token_id, &grantee_addr))?
+            .unwrap_or_default();
+        grants.retain(|grant| grant.role != role);
+        if grants.is_empty() {
+            self.roles.remove(deps.storage, (&token_id, &grantee_addr));
+        } else {
+            self.roles
+                .save(deps.storage, (&token_id, &grantee_addr), &grants)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_role")
+            .add_attribute("sender", info.sender)
+            .add_attribute("grantee", grantee)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn update_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        action: cw_ownable::Action,
+    ) -> Result<Response<C>, ContractError> {
+        let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+        Ok(Response::new().add_attributes(ownership.into_attributes()))
+    }
+}
+
+impl<'a, T, C, E, Q> Cw721Execute<T, C> for Cw721Contract<'a, T, C, E, Q>
+where
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    type Err = ContractError;
+
+    fn transfer_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        self._transfer_nft(deps, &env, &info, &recipient, &token_id, false)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn send_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    ) -> Result<Response<C>, ContractError> {
+        // Transfer token
+        self._transfer_nft(deps, &env, &info, &contract, &token_id, false)?;
+
+        let send = Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id: token_id.clone(),
+            msg,
+        };
+
+        // Send message
+        Ok(Response::new()
+            .add_message(send.into_cosmos_msg(contract.clone())?)
+            .add_attribute("action", "send_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn approve(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        self._update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "approve")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn revoke(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        self._update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn approve_all(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        // reject expired data as invalid
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+
+        // set the operator for us
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        self.operators
+            .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "approve_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator))
+    }
+
+    fn revoke_all(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        operator: String,
+    ) -> Result<Response<C>, ContractError> {
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        self.operators
+            .remove(deps.storage, (&info.sender, &operator_addr));
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator))
+    }
+
+    fn burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+
+        // every refund path needs the token, so it has to outlive its escrow
+        if !token.shortterm_rental.travelers.is_empty()
+            || token.longterm_rental.deposit_amount > token.longterm_rental.withdrawn_amount
+            || !self.token_offers(deps.storage, &token_id)?.is_empty()
+            || self.auctions.has(deps.storage, &token_id)
+            || self.fractions.has(deps.storage, &token_id)
+        {
+            return Err(ContractError::EscrowedFunds {});
+        }
+
+        self.remove_token(deps.storage, &token_id)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("burn").add_attribute("owner", token.owner.to_string()),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "burn")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+}
 
 // helpers
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
 {
+    /// Tokens with reservations only move with `carry_reservations`, in which case the
+    /// recipient inherits the bookings and the rent earned so far is settled to the
+    /// current owner through the returned settlement.
     pub fn _transfer_nft(
         &self,
         deps: DepsMut,
@@ -495,37 +2433,465 @@ where
         info: &MessageInfo,
         recipient: &str,
         token_id: &str,
-    ) -> Result<TokenInfo<T>, ContractError> {
+        carry_reservations: bool,
+    ) -> Result<(TokenInfo<T>, Settlement), ContractError> {
         let mut token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
+        if self.auctions.has(deps.storage, token_id) {
+            return Err(ContractError::AuctionActive {});
+        }
+        if self.is_fractionalized(deps.storage, token_id)? {
+            return Err(ContractError::Fractionalized {});
+        }
+        let mut settlement = Settlement::default();
+        if !token.shortterm_rental.travelers.is_empty() {
+            if !carry_reservations {
+                return Err(ContractError::OutstandingReservations {});
+            }
+            settlement = self.settle_earned_rent(deps.storage, env, token_id, &mut token)?;
+        }
         // set owner and remove existing approvals
+        let previous_owner = token.owner;
         token.owner = deps.api.addr_validate(recipient)?;
         token.approvals = vec![];
+        // stays that already ended were hosted by the previous owner
+        let current_time = env.block.time.seconds();
+        for traveler in token.shortterm_rental.travelers.iter_mut() {
+            if traveler.renting_period[1] > current_time {
+                traveler.host = Some(token.owner.clone());
+            } else if traveler.host.is_none() {
+                traveler.host = Some(previous_owner.clone());
+            }
+        }
         self.tokens.save(deps.storage, token_id, &token)?;
-        Ok(token)
+        // payout preferences and roles belong to the previous owner
+        self.clear_owner_settings(deps.storage, token_id)?;
+        self.log_provenance(
+            deps.storage,
+            env,
+            token_id,
+            Event::new("transfer")
+                .add_attribute("from", previous_owner.into_string())
+                .add_attribute("to", token.owner.to_string()),
+        )?;
+        Ok((token, settlement))
+    }
+
+    /// Removes the payout preferences, sale listing and roles the owner set up for a token
+    pub fn clear_owner_settings(&self, storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+        self.token_payouts.remove(storage, token_id);
+        self.sales.remove(storage, token_id);
+        self.unlink_template(storage, token_id)?;
+        let grantees = self
+            .roles
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for grantee in grantees {
+            self.roles.remove(storage, (token_id, &grantee));
+        }
+        Ok(())
+    }
+
+    /// Appends the current token_uri and extension of a token to its metadata history
+    pub fn record_metadata_version(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: &str,
+        token: &TokenInfo<T>,
+        editor: &Addr,
+    ) -> StdResult<u64> {
+        let version = self
+            .metadata_history
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map_or(1, |last| last + 1);
+        self.metadata_history.save(
+            storage,
+            (token_id, version),
+            &MetadataVersion {
+                version,
+                editor: editor.clone(),
+                updated_at: env.block.time.seconds(),
+                token_uri: token.token_uri.clone(),
+                extension: token.extension.clone(),
+            },
+        )?;
+        self.log_provenance(
+            storage,
+            env,
+            token_id,
+            Event::new("metadata")
+                .add_attribute("editor", editor.to_string())
+                .add_attribute("version", version.to_string())
+                .add_attribute("token_uri", token.token_uri.clone().unwrap_or_default()),
+        )?;
+        Ok(version)
+    }
+
+    pub fn unlink_template(&self, storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+        if let Some((host, template_id)) = self.template_links.may_load(storage, token_id)? {
+            self.template_links.remove(storage, token_id);
+            self.template_tokens
+                .remove(storage, (&host, &template_id, token_id));
+        }
+        Ok(())
+    }
+
+    /// Lists a token with the terms of a template, keeping its available period
+    pub fn apply_template(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: &str,
+        token: &mut TokenInfo<T>,
+        template: &ListingTemplate,
+    ) -> StdResult<()> {
+        token.shortterm_rental.islisted = Some(true);
+        token.shortterm_rental.denom = template.denom.clone();
+        token.shortterm_rental.price_per_day = template.price_per_day;
+        token.shortterm_rental.auto_approve = template.auto_approve;
+        token.shortterm_rental.minimum_stay = template.minimum_stay;
+        token.shortterm_rental.cancellation = template.cancellation.clone();
+        self.tokens.save(storage, token_id, token)?;
+        self.log_provenance(
+            storage,
+            env,
+            token_id,
+            Event::new("listing")
+                .add_attribute("market", "short_term")
+                .add_attribute("listed", "true")
+                .add_attribute("price_per_day", template.price_per_day.to_string())
+                .add_attribute("denom", template.denom.clone()),
+        )
+    }
+
+    /// Appends `event` to the provenance log of a token, stamped with the current block
+    pub fn log_provenance(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: &str,
+        event: Event,
+    ) -> StdResult<()> {
+        let seq = self
+            .provenance
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map_or(1, |last| last + 1);
+        let event = event
+            .add_attribute("height", env.block.height.to_string())
+            .add_attribute("time", env.block.time.seconds().to_string());
+        self.provenance.save(storage, (token_id, seq), &event)
+    }
+
+    /// Deletes a token together with everything stored about it
+    pub fn remove_token(&self, storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+        self.tokens.remove(storage, token_id)?;
+        self.decrement_tokens(storage)?;
+        self.clear_owner_settings(storage, token_id)?;
+        self.verifications.remove(storage, token_id);
+        self.royalties.remove(storage, token_id);
+        let versions = self
+            .metadata_history
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for version in versions {
+            self.metadata_history.remove(storage, (token_id, version));
+        }
+        Ok(())
+    }
+
+    /// Hands a token sold for `price`, already paid into the contract, to `buyer`.
+    /// The platform fee goes to the treasury, the royalty to its receiver and the rest to
+    /// the seller. Bookings move to the buyer with their earned rent settled to the seller.
+    pub fn _sell(
+        &self,
+        mut deps: DepsMut,
+        env: &Env,
+        token_id: &str,
+        buyer: &Addr,
+        price: Coin,
+    ) -> Result<Settlement, ContractError> {
+        let seller = self.tokens.load(deps.storage, token_id)?.owner;
+        let fee_amount = price
+            .amount
+            .multiply_ratio(self.get_fee(deps.storage)?, 10000u64);
+        let royalty = self.royalty(deps.storage, token_id)?;
+        // the royalty gets what is left after the fee, so a sale can always settle
+        let royalty_amount = royalty
+            .as_ref()
+            .map(|royalty| royalty.amount(price.amount))
+            .unwrap_or_default()
+            .min(price.amount - fee_amount);
+        let seller_amount = price.amount - fee_amount - royalty_amount;
+
+        // the sale acts on behalf of the seller
+        let seller_info = MessageInfo {
+            sender: seller.clone(),
+            funds: vec![],
+        };
+        let (_, mut settlement) = self._transfer_nft(
+            deps.branch(),
+            env,
+            &seller_info,
+            buyer.as_str(),
+            token_id,
+            true,
+        )?;
+
+        if !fee_amount.is_zero() {
+            settlement.events.push(self.treasury_deposit(
+                deps.storage,
+                &Coin {
+                    denom: price.denom.clone(),
+                    amount: fee_amount,
+                },
+            )?);
+        }
+        if let Some(royalty) = royalty.filter(|_| !royalty_amount.is_zero()) {
+            settlement.messages.push(BankMsg::Send {
+                to_address: royalty.payment_address.into_string(),
+                amount: vec![Coin {
+                    denom: price.denom.clone(),
+                    amount: royalty_amount,
+                }],
+            });
+        }
+        if !seller_amount.is_zero() {
+            settlement.messages.push(BankMsg::Send {
+                to_address: seller.into_string(),
+                amount: vec![Coin {
+                    denom: price.denom,
+                    amount: seller_amount,
+                }],
+            });
+        }
+        Ok(settlement)
+    }
+
+    /// Pays the current owner what its bookings earned so far: the forfeited part of
+    /// cancelled bookings and the elapsed share of ongoing stays. Bookings that have not
+    /// started yet stay in escrow for the next owner.
+    pub fn settle_earned_rent(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: &str,
+        token: &mut TokenInfo<T>,
+    ) -> StdResult<Settlement> {
+        let current_time = env.block.time.seconds();
+        let fee_percentage = self.get_fee(storage)?;
+        let denom = token.shortterm_rental.denom.clone();
+        let mut settlement = Settlement::default();
+        let mut host_amount = Uint128::zero();
+
+        for traveler in token.shortterm_rental.travelers.iter_mut() {
+            if traveler.cancelled {
+                host_amount += traveler.deposit_amount;
+                traveler.deposit_amount = Uint128::zero();
+                continue;
+            }
+            let check_in = traveler.renting_period[0].max(traveler.settled_until);
+            let check_out = traveler.renting_period[1];
+            if !traveler.approved || check_in >= current_time {
+                continue;
+            }
+            let earned = if current_time >= check_out || check_in >= check_out {
+                traveler.deposit_amount
+            } else {
+                traveler
+                    .deposit_amount
+                    .multiply_ratio(current_time - check_in, check_out - check_in)
+            };
+            traveler.deposit_amount -= earned;
+            traveler.settled_until = current_time;
+
+            let fee_amount = earned.multiply_ratio(fee_percentage, 10000u64);
+            if !fee_amount.is_zero() {
+                settlement.events.push(self.treasury_deposit(
+                    storage,
+                    &Coin {
+                        denom: denom.clone(),
+                        amount: fee_amount,
+                    },
+                )?);
+            }
+            host_amount += earned - fee_amount;
+        }
+
+        if !host_amount.is_zero() {
+            settlement.messages = self.payout_msgs(
+                storage,
+                token_id,
+                &token.owner,
+                Coin {
+                    denom,
+                    amount: host_amount,
+                },
+            )?;
+        }
+        Ok(settlement)
+    }
+
+    /// Pays `amount` of host proceeds for `token_id` according to its payout config.
+    /// Shares of a held config are credited for `ClaimPayout` instead of sent.
+    pub fn payout_msgs(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        owner: &Addr,
+        amount: Coin,
+    ) -> StdResult<Vec<BankMsg>> {
+        // rent of a fractionalized token in the buyout denom goes to its shareholders
+        if let Some(mut fraction) = self.fractions.may_load(storage, token_id)? {
+            if fraction.bought_out_by.is_none() && fraction.buyout_price.denom == amount.denom {
+                // the index never credits more than was paid in altogether, what it
+                // rounds off waits for the next distribution
+                let total = amount.amount + fraction.undistributed;
+                let credited = fraction.total_shares * fraction.dividend_index;
+                fraction.dividend_index += Decimal::from_ratio(total, fraction.total_shares);
+                fraction.undistributed =
+                    credited + total - fraction.total_shares * fraction.dividend_index;
+                self.fractions.save(storage, token_id, &fraction)?;
+                return Ok(vec![]);
+            }
+        }
+        let config = self.payout_config(storage, token_id, owner)?;
+        let mut msgs = vec![];
+        let mut remaining = amount.amount;
+        for (i, share) in config.shares.iter().enumerate() {
+            // the last share takes the rounding remainder
+            let part = if i == config.shares.len() - 1 {
+                remaining
+            } else {
+                amount.amount.multiply_ratio(share.percentage, 100u64)
+            };
+            remaining -= part;
+            if part.is_zero() {
+                continue;
+            }
+            let part = Coin {
+                denom: amount.denom.clone(),
+                amount: part,
+            };
+            if config.hold {
+                self.add_claimable(storage, &share.address, &part)?;
+            } else {
+                msgs.push(BankMsg::Send {
+                    to_address: share.address.to_string(),
+                    amount: vec![part],
+                });
+            }
+        }
+        Ok(msgs)
+    }
+
+    pub fn setmetadata(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        token_uri: String,
+        // expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        // ensure we have permissions
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::MetadataEditor,
+        )?;
+        self.check_can_edit(&env, &token)?;
+        token.token_uri = Some(token_uri);
+        self.tokens.save(deps.storage, &token_id, &token)?;
+        let version =
+            self.record_metadata_version(deps.storage, &env, &token_id, &token, &info.sender)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "setmetadata")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("version", version.to_string()))
+    }
+
+    pub fn update_extension(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        extension: T,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::MetadataEditor,
+        )?;
+        self.check_can_edit(&env, &token)?;
+        if let Some(property) = extension.property() {
+            property.validate()?;
+        }
+        token.extension = extension;
+        self.tokens.save(deps.storage, &token_id, &token)?;
+        let version =
+            self.record_metadata_version(deps.storage, &env, &token_id, &token, &info.sender)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_extension")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("version", version.to_string()))
     }
 
-    pub fn setmetadata(
+    pub fn update_property(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
         token_id: String,
-        token_uri: String,
-        // expires: Option<Expiration>,
+        update: PropertyUpdate,
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
-        // ensure we have permissions
-        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::MetadataEditor,
+        )?;
         self.check_can_edit(&env, &token)?;
-        token.token_uri = Some(token_uri);
+        let property = token
+            .extension
+            .property_mut()
+            .ok_or(ContractError::MissingPropertyMetadata {})?;
+        update.apply(property);
+        property.validate()?;
         self.tokens.save(deps.storage, &token_id, &token)?;
+        let version =
+            self.record_metadata_version(deps.storage, &env, &token_id, &token, &info.sender)?;
 
         Ok(Response::new()
-            .add_attribute("action", "setmetadata")
+            .add_attribute("action", "update_property")
             .add_attribute("sender", info.sender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("token_id", token_id)
+            .add_attribute("version", version.to_string()))
     }
 
     pub fn setlistforshorttermrental(
@@ -543,8 +2909,17 @@ where
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
         // ensure we have permissions
-        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::PricingManager,
+        )?;
         self.check_can_edit(&env, &token)?;
+        self.check_verified(deps.storage, &token_id)?;
+        self.check_rent_denom(deps.storage, &token_id, &denom)?;
 
         token.shortterm_rental.islisted = Some(true);
         token.shortterm_rental.price_per_day = price_per_day;
@@ -554,6 +2929,17 @@ where
         token.shortterm_rental.minimum_stay = minimum_stay;
         token.shortterm_rental.cancellation = cancellation;
         self.tokens.save(deps.storage, &token_id, &token)?;
+        self.unlink_template(deps.storage, &token_id)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("listing")
+                .add_attribute("market", "short_term")
+                .add_attribute("listed", "true")
+                .add_attribute("price_per_day", price_per_day.to_string())
+                .add_attribute("denom", token.shortterm_rental.denom),
+        )?;
 
         Ok(Response::new()
             .add_attribute("action", "setlistforshorttermrental")
@@ -570,7 +2956,14 @@ where
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
         // ensure we have permissions
-        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::PricingManager,
+        )?;
         self.check_can_edit(&env, &token)?;
 
         token.shortterm_rental.islisted = None;
@@ -580,8 +2973,25 @@ where
         token.shortterm_rental.minimum_stay = 0u64;
         token.shortterm_rental.cancellation = vec![];
         token.shortterm_rental.denom = "".to_string();
+        token.shortterm_rental.maximum_stay = 0;
+        token.shortterm_rental.booking_horizon = 0;
+        token.shortterm_rental.advance_notice = 0;
+        token.shortterm_rental.buffer_days = 0;
+        token.shortterm_rental.base_guests = 0;
+        token.shortterm_rental.extra_guest_fee = 0;
+        token.shortterm_rental.add_ons = vec![];
+        token.shortterm_rental.min_kyc_level = 0;
 
         self.tokens.save(deps.storage, &token_id, &token)?;
+        self.unlink_template(deps.storage, &token_id)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("listing")
+                .add_attribute("market", "short_term")
+                .add_attribute("listed", "false"),
+        )?;
 
         Ok(Response::new()
             .add_attribute("action", "setunlistforshorttermrental")
@@ -589,106 +2999,237 @@ where
             .add_attribute("token_id", token_id))
     }
 
-    pub fn setreservationforshortterm(
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_stay_rules(
         &self,
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         token_id: String,
-        renting_period: Vec<String>,
+        maximum_stay: u64,
+        booking_horizon: u64,
+        advance_notice: u64,
+        buffer_days: u64,
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
-        // let new_checkin = NaiveDate::parse_from_str(&renting_period[0], "%Y/%m/%d").unwrap();
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::PricingManager,
+        )?;
+        if token.shortterm_rental.islisted.is_none() {
+            return Err(ContractError::NotListed {});
+        }
+        if maximum_stay != 0 && maximum_stay < token.shortterm_rental.minimum_stay {
+            return Err(ContractError::InvalidInput {});
+        }
 
+        token.shortterm_rental.maximum_stay = maximum_stay;
+        token.shortterm_rental.booking_horizon = booking_horizon;
+        token.shortterm_rental.advance_notice = advance_notice;
+        token.shortterm_rental.buffer_days = buffer_days;
+        self.tokens.save(deps.storage, &token_id, &token)?;
 
-        let new_checkin = renting_period[0].parse::<u64>();
-        let new_checkin_timestamp;
+        Ok(Response::new()
+            .add_attribute("action", "set_stay_rules")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
 
-        match new_checkin {
-            Ok(timestamp) => {
-                new_checkin_timestamp = timestamp;
-            }
-            Err(_e) => {
-                return Err(ContractError::NotReserved {});
-            }
-        }
-        let new_checkout = renting_period[1].parse::<u64>();
-        let new_checkout_timestamp;
+    pub fn set_guest_kyc_level(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        min_level: u8,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::ReservationManager,
+        )?;
+
+        token.shortterm_rental.min_kyc_level = min_level;
+        self.tokens.save(deps.storage, &token_id, &token)?;
 
-        match new_checkout {
-            Ok(timestamp) => {
-                new_checkout_timestamp = timestamp;
-            }
-            Err(_e) => {
-                return Err(ContractError::NotReserved {});
-            }
-        }
+        Ok(Response::new()
+            .add_attribute("action", "set_guest_kyc_level")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("min_level", min_level.to_string()))
+    }
 
-        if ((new_checkout_timestamp - new_checkin_timestamp)/ 86400) < token.shortterm_rental.minimum_stay {
-            return Err(ContractError::LessThanMinimum {});
-        }
+    pub fn set_guest_pricing(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        base_guests: u32,
+        extra_guest_fee: u64,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::PricingManager,
+        )?;
+
+        token.shortterm_rental.base_guests = base_guests;
+        token.shortterm_rental.extra_guest_fee = extra_guest_fee;
+        self.tokens.save(deps.storage, &token_id, &token)?;
 
-        let mut placetoreserve: i32 = -1;
-        let lenoftravelers = token.shortterm_rental.travelers.len();
-
-        let mut flag = false;
-        for (i, traveler) in token.shortterm_rental.travelers.iter().enumerate() {
-            let checkin = traveler.renting_period[0];
-            let checkout = traveler.renting_period[1];
-            if new_checkout_timestamp < checkin {
-                if i == 0 {
-                    placetoreserve = 0;
-                    break;
-                } else if flag {
-                    placetoreserve = i as i32;
-                    break;
-                }
-            } else if checkout < new_checkin_timestamp {
-                flag = true;
-                if i == lenoftravelers - 1 {
-                    placetoreserve = lenoftravelers as i32;
-                    break;
-                }
-            } else {
-                flag = false;
+        Ok(Response::new()
+            .add_attribute("action", "set_guest_pricing")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("base_guests", base_guests.to_string())
+            .add_attribute("extra_guest_fee", extra_guest_fee.to_string()))
+    }
+
+    pub fn set_add_ons(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        add_ons: Vec<AddOn>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::PricingManager,
+        )?;
+        if add_ons.len() > MAX_ADD_ONS {
+            return Err(ContractError::InvalidInput {});
+        }
+        for (i, add_on) in add_ons.iter().enumerate() {
+            if add_on.id.trim().is_empty() || add_ons[..i].iter().any(|other| other.id == add_on.id)
+            {
+                return Err(ContractError::InvalidInput {});
             }
         }
 
-        if placetoreserve == -1 {
-            if lenoftravelers > 0 {
-                return Err(ContractError::UnavailablePeriod {});
-            } else {
-                placetoreserve = 0;
-            }
+        token.shortterm_rental.add_ons = add_ons;
+        self.tokens.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_add_ons")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn setreservationforshortterm(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        renting_period: Vec<String>,
+        guests: Option<u32>,
+        add_ons: Vec<String>,
+        voucher: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        // let new_checkin = NaiveDate::parse_from_str(&renting_period[0], "%Y/%m/%d").unwrap();
+
+        let (new_checkin_timestamp, new_checkout_timestamp) =
+            parse_renting_period(&renting_period)?;
+        let placetoreserve = self.check_stay_rules(
+            &env,
+            &token.shortterm_rental,
+            new_checkin_timestamp,
+            new_checkout_timestamp,
+        )?;
+        if self.global_blocklist.has(deps.storage, &info.sender)
+            || self
+                .host_blocklists
+                .has(deps.storage, (&token.owner, &info.sender))
+        {
+            return Err(ContractError::Blocked {});
+        }
+        let min_kyc_level = token.shortterm_rental.min_kyc_level;
+        if min_kyc_level > 0
+            && !self
+                .attestations
+                .may_load(deps.storage, &info.sender)?
+                .map_or(false, |attestation| {
+                    // attestations of removed providers no longer count
+                    self.kyc_providers.has(deps.storage, &attestation.provider)
+                        && attestation.satisfies(&env.block, min_kyc_level)
+                })
+        {
+            return Err(ContractError::KycRequired {
+                level: min_kyc_level,
+            });
         }
 
+        let mut quote = self.quote_reservation(
+            deps.storage,
+            &token,
+            guests,
+            &add_ons,
+            new_checkin_timestamp,
+            new_checkout_timestamp,
+        )?;
+        let redeemed = voucher
+            .as_deref()
+            .map(|code| self.apply_voucher(deps.storage, &env, &token_id, &token, code, &mut quote))
+            .transpose()?;
         if info.funds[0].denom != token.shortterm_rental.denom {
             return Err(ContractError::InvalidDeposit {});
         }
         let sent_amount = info.funds[0].amount;
-        let fee_percentage = self.get_fee(deps.storage)?;
-        let rent_amount = token.shortterm_rental.price_per_day
-        * (new_checkout_timestamp - new_checkin_timestamp)/(86400);
-        if sent_amount
-            < Uint128::from(rent_amount) + Uint128::new((u128::from(rent_amount) * u128::from(fee_percentage)) / 10000)
-        {
+        if sent_amount < quote.total {
             return Err(ContractError::InsufficientDeposit {});
         }
+        if let (Some(code), Some(mut redeemed)) = (&voucher, redeemed) {
+            redeemed.uses += 1;
+            self.vouchers
+                .save(deps.storage, (&redeemed.issuer, code), &redeemed)?;
+        }
 
-        self.increase_balance(deps.storage, info.funds[0].denom.clone(), sent_amount - Uint128::from(rent_amount))?;
+        let treasury_event = self.treasury_deposit(
+            deps.storage,
+            &Coin {
+                denom: info.funds[0].denom.clone(),
+                amount: sent_amount - quote.deposit,
+            },
+        )?;
 
         let traveler = Traveler {
-            deposit_amount: Uint128::from(rent_amount),
+            deposit_amount: quote.deposit,
             renting_period: vec![new_checkin_timestamp, new_checkout_timestamp],
             address: Some(info.sender.clone()),
             approved: token.shortterm_rental.auto_approve,
-            cancelled:false
+            cancelled:false,
+            settled_until: 0,
+            guests: quote.guests,
+            add_ons: quote.add_ons,
+            host: Some(token.owner.clone()),
         };
 
         // token.shortterm_rental.deposit_amount += sent_amount;
         token
             .shortterm_rental
             .travelers
-            .insert(placetoreserve as usize, traveler);
+            .insert(placetoreserve, traveler);
 
         self.tokens.save(deps.storage, &token_id, &token)?;
 
@@ -705,10 +3246,17 @@ where
         //             }],
         //         }))
         // } else {
-            Ok(Response::new()
-                .add_attribute("action", "setreservationforshortterm")
-                .add_attribute("sender", info.sender)
-                .add_attribute("token_id", token_id))
+        let mut response = Response::new()
+            .add_attribute("action", "setreservationforshortterm")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_event(treasury_event);
+        if let Some(code) = voucher {
+            response = response
+                .add_attribute("voucher", code)
+                .add_attribute("discount", quote.discount);
+        }
+        Ok(response)
         // }
     }
 
@@ -722,7 +3270,14 @@ where
         traveler: String,
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
-        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::ReservationManager,
+        )?;
 
         // let is_approved = token.shortterm_rental.auto_approve;
 
@@ -794,7 +3349,14 @@ where
         renting_period: Vec<String>,
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
-        self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+        self.check_has_role(
+            deps.as_ref(),
+            &env,
+            &info,
+            &token_id,
+            &token,
+            TokenRole::ReservationManager,
+        )?;
 
         // if token.shortterm_rental.auto_approve {
         //     return Err(ContractError::ApprovedAlready {});
@@ -854,6 +3416,7 @@ where
 
         let mut position: i32 = -1;
         let mut amount = Uint128::new(0);
+        let mut add_ons = vec![];
         let traveler_address = info.sender.to_string();
         for (i, item) in token.shortterm_rental.travelers.iter().enumerate() {
             if item.address == Some(info.sender.clone()) 
@@ -864,6 +3427,7 @@ where
                 if item.approved && !item.cancelled {
                     position = i as i32;
                     amount = item.deposit_amount;
+                    add_ons = item.add_ons.clone();
                 } else {
                     return Err(ContractError::NotApproved {});
                 }
@@ -891,16 +3455,34 @@ where
             return Err(ContractError::RentalAlreadyStarted {});
         }
 
+        // add-ons are taken out of the deposit and refunded by their own rule
+        let add_on_amount = add_ons
+            .iter()
+            .fold(Uint128::zero(), |total, add_on| total + add_on.amount);
+        let rent_amount = amount - add_on_amount;
+
         let diff_days = (check_in_time_timestamp - current_time)/86400;
+        let mut percentage = 0u64;
         for (_i, item) in cancellation.iter().enumerate() {
             if item.deadline < diff_days {
-                refundable_amount =  Uint128::new((amount.u128() * u128::from(item.percentage)) / 100);
+                percentage = item.percentage;
+                refundable_amount =
+                    Uint128::new((rent_amount.u128() * u128::from(item.percentage)) / 100);
                 break;
             }
         }
 
         if cancellation.len() == 0 {
-            refundable_amount = amount;
+            percentage = 100;
+            refundable_amount = rent_amount;
+        }
+
+        for add_on in add_ons {
+            refundable_amount += match add_on.refund {
+                AddOnRefund::Full => add_on.amount,
+                AddOnRefund::Policy => add_on.amount.multiply_ratio(percentage, 100u64),
+                AddOnRefund::None => Uint128::zero(),
+            };
         }
 
 
@@ -1001,7 +3583,20 @@ where
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
 
-        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        // cw721 spenders of the token can finalize as well as reservation managers
+        if self
+            .check_can_send(deps.as_ref(), &env, &info, &token)
+            .is_err()
+        {
+            self.check_has_role(
+                deps.as_ref(),
+                &env,
+                &info,
+                &token_id,
+                &token,
+                TokenRole::ReservationManager,
+            )?;
+        }
 
         let mut position: i32 = -1;
         let mut amount = Uint128::from(0u64);
@@ -1025,7 +3620,10 @@ where
             return Err(ContractError::RentalActive {});
         }
 
-        let mut target = "".to_string();
+        let mut to_host = false;
+        let mut completed = false;
+        let mut host = token.owner.clone();
+        let mut treasury_events = vec![];
 
         for (i, item) in token.shortterm_rental.travelers.iter().enumerate() {
             if item.address == Some(Addr::unchecked(traveler.clone()))
@@ -1036,19 +3634,31 @@ where
                 position = i as i32;
                 if item.cancelled {
                     amount = item.deposit_amount;
-                    target = token.owner.to_string();
+                    to_host = true;
                 }
                 if !item.cancelled {
                     if !item.approved {
                         amount = item.deposit_amount;
-                        target = traveler.clone();
                         // return  Err(ContractError::NotApproved {});
                     }
                     if item.approved {
-                        target = token.owner.to_string();
+                        amount = item.deposit_amount;
+                        to_host = true;
+                        completed = true;
+                        if let Some(item_host) = &item.host {
+                            host = item_host.clone();
+                        }
                         let fee_percentage = self.get_fee(deps.storage)?;
-                        self.increase_balance(deps.storage, token.shortterm_rental.denom.clone(), Uint128::new((u128::from(amount) * u128::from(fee_percentage)) / 10000))?;
-                        amount -= Uint128::new((u128::from(amount) * u128::from(fee_percentage)) / 10000);
+                        let fee_amount =
+                            Uint128::new((u128::from(amount) * u128::from(fee_percentage)) / 10000);
+                        treasury_events.push(self.treasury_deposit(
+                            deps.storage,
+                            &Coin {
+                                denom: token.shortterm_rental.denom.clone(),
+                                amount: fee_amount,
+                            },
+                        )?);
+                        amount -= fee_amount;
                     }
                 }
 
@@ -1061,28 +3671,130 @@ where
             token.shortterm_rental.travelers.remove(position as usize);
             self.tokens.save(deps.storage, &token_id, &token)?;
         }
+        if completed {
+            let (check_in, check_out) = parse_renting_period(&renting_period)?;
+            self.completed_stays.save(
+                deps.storage,
+                (&token_id, &Addr::unchecked(traveler.clone()), check_in),
+                &CompletedStay { host, check_out },
+            )?;
+            self.log_provenance(
+                deps.storage,
+                &env,
+                &token_id,
+                Event::new("stay_completed")
+                    .add_attribute("traveler", traveler.clone())
+                    .add_attribute("check_in", renting_period[0].clone())
+                    .add_attribute("check_out", renting_period[1].clone()),
+            )?;
+        }
 
+        let mut messages = vec![];
         if amount > Uint128::new(0) {
+            let payment = Coin {
+                denom: token.shortterm_rental.denom,
+                amount,
+            };
+            if to_host {
+                messages = self.payout_msgs(deps.storage, &token_id, &token.owner, payment)?;
+            } else {
+                messages.push(BankMsg::Send {
+                    to_address: traveler,
+                    amount: vec![payment],
+                });
+            }
+        }
+
         Ok(Response::new()
             .add_attribute("action", "finalizeshorttermrental")
             .add_attribute("sender", info.sender)
             .add_attribute("token_id", token_id)
-            .add_message(BankMsg::Send {
-                to_address: target.clone(),
-                amount: vec![Coin {
-                    denom: token.shortterm_rental.denom,
-                    amount: amount,
-                }],
-            }))            
-        } 
-        else {
-            Ok(Response::new()
-            .add_attribute("action", "finalizeshorttermrental")
-            .add_attribute("sender", info.sender)
-            .add_attribute("token_id", token_id)
-            )
+            .add_events(treasury_events)
+            .add_messages(messages))
+
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn review_stay(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        traveler: String,
+        renting_period: Vec<String>,
+        rating: u8,
+        comment_uri: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        if !(1..=5).contains(&rating)
+            || comment_uri
+                .as_ref()
+                .map_or(false, |uri| uri.trim().is_empty() || uri.len() > 256)
+        {
+            return Err(ContractError::InvalidInput {});
+        }
+        let traveler = deps.api.addr_validate(&traveler)?;
+        let (check_in, check_out) = parse_renting_period(&renting_period)?;
+        let stay = self.reviewable_stay(
+            deps.storage,
+            &env,
+            &token_id,
+            &traveler,
+            check_in,
+            check_out,
+        )?;
+        let from_traveler = info.sender == traveler;
+        let reviewee = if from_traveler {
+            stay.host
+        } else if info.sender == stay.host {
+            traveler.clone()
+        } else {
+            return Err(ContractError::NotStayParty {});
+        };
+        if env.block.time.seconds() > stay.check_out + REVIEW_WINDOW {
+            return Err(ContractError::ReviewWindowClosed {});
+        }
+
+        let key = (token_id.as_str(), check_in, &info.sender);
+        if self.reviews.has(deps.storage, key) {
+            return Err(ContractError::AlreadyReviewed {});
+        }
+        self.reviews.save(
+            deps.storage,
+            key,
+            &Review {
+                reviewee: reviewee.clone(),
+                rating,
+                comment_uri,
+                created_at: env.block.time.seconds(),
+            },
+        )?;
+        self.address_ratings
+            .update(deps.storage, &reviewee, |summary| -> StdResult<_> {
+                Ok(summary.unwrap_or_default().with_rating(rating))
+            })?;
+        if from_traveler {
+            self.token_ratings
+                .update(deps.storage, &token_id, |summary| -> StdResult<_> {
+                    Ok(summary.unwrap_or_default().with_rating(rating))
+                })?;
         }
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("review")
+                .add_attribute("author", info.sender.clone())
+                .add_attribute("reviewee", reviewee.clone())
+                .add_attribute("rating", rating.to_string()),
+        )?;
 
+        Ok(Response::new()
+            .add_attribute("action", "review_stay")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("reviewee", reviewee)
+            .add_attribute("rating", rating.to_string()))
     }
 
     pub fn setlistforlongtermrental(
@@ -1100,6 +3812,7 @@ where
         let mut token = self.tokens.load(deps.storage, &token_id)?;
         // ensure we have permissions
         self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+        self.check_verified(deps.storage, &token_id)?;
 
         let landlord = Landlord {
             denom: denom,
@@ -1111,6 +3824,15 @@ where
         token.longterm_rental.islisted = Some(islisted);
         token.longterm_rental.landlord = Some(landlord);
         self.tokens.save(deps.storage, &token_id, &token)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("listing")
+                .add_attribute("market", "long_term")
+                .add_attribute("listed", islisted.to_string())
+                .add_attribute("price_per_month", price_per_month.to_string()),
+        )?;
 
         Ok(Response::new()
             .add_attribute("action", "setlistforlongtermrental")
@@ -1132,6 +3854,14 @@ where
         token.longterm_rental.islisted = None;
         token.longterm_rental.landlord = None;
         self.tokens.save(deps.storage, &token_id, &token)?;
+        self.log_provenance(
+            deps.storage,
+            &env,
+            &token_id,
+            Event::new("listing")
+                .add_attribute("market", "long_term")
+                .add_attribute("listed", "false"),
+        )?;
 
         Ok(Response::new()
             .add_attribute("action", "setunlistforlongtermrental")
@@ -1320,12 +4050,15 @@ where
 
         token.longterm_rental.withdrawn_amount += amount;
         self.tokens.save(deps.storage, &token_id, &token)?;
+        let messages = self.payout_msgs(
+            deps.storage,
+            &token_id,
+            &token.owner,
+            Coin { denom, amount },
+        )?;
         Ok(Response::new()
             .add_attribute("action", "withdrawtolandlord")
-            .add_message(BankMsg::Send {
-                to_address: token.owner.into_string(),
-                amount: vec![Coin { denom, amount }],
-            }))
+            .add_messages(messages))
     }
 
     pub fn finalizelongtermrental(
@@ -1337,6 +4070,14 @@ where
     ) -> Result<Response<C>, ContractError> {
         let mut token = self.tokens.load(deps.storage, &token_id)?;
         self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        if let Some(tenant) = &token.longterm_rental.tenant_address {
+            self.log_provenance(
+                deps.storage,
+                &env,
+                &token_id,
+                Event::new("tenancy_completed").add_attribute("tenant", tenant.to_string()),
+            )?;
+        }
 
         token.longterm_rental.isreserved = None;
         token.longterm_rental.tenant = None;
@@ -1420,6 +4161,248 @@ where
         }
     }
 
+    /// Owner and operators pass, anyone else needs an unexpired grant of `role` on the token
+    pub fn check_has_role(
+        &self,
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        token_id: &str,
+        token: &TokenInfo<T>,
+        role: TokenRole,
+    ) -> Result<(), ContractError> {
+        if self.check_can_approve(deps, env, info, token).is_ok() {
+            return Ok(());
+        }
+        let grants = self
+            .roles
+            .may_load(deps.storage, (token_id, &info.sender))?
+            .unwrap_or_default();
+        if grants
+            .iter()
+            .any(|grant| grant.role == role && !grant.expires.is_expired(&env.block))
+        {
+            Ok(())
+        } else {
+            Err(ContractError::MissingRole {})
+        }
+    }
+
+    /// Fails for unverified tokens while the contract requires verification for listing
+    pub fn check_verified(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+    ) -> Result<(), ContractError> {
+        if self.is_verification_required(storage)? && !self.verifications.has(storage, token_id) {
+            return Err(ContractError::NotVerified {});
+        }
+        Ok(())
+    }
+
+    /// Fails when rent in `denom` couldn't be shared out to the shareholders of `token_id`
+    pub fn check_rent_denom(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        denom: &str,
+    ) -> Result<(), ContractError> {
+        if let Some(fraction) = self.fractions.may_load(storage, token_id)? {
+            if fraction.bought_out_by.is_none() && fraction.buyout_price.denom != denom {
+                return Err(ContractError::DenomMismatch {
+                    denom: fraction.buyout_price.denom,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Price of a stay for `guests` with the chosen and mandatory add-ons, checked
+    /// against the property's occupancy
+    pub fn quote_reservation(
+        &self,
+        storage: &dyn Storage,
+        token: &TokenInfo<T>,
+        guests: Option<u32>,
+        add_ons: &[String],
+        check_in: u64,
+        check_out: u64,
+    ) -> Result<ReservationQuoteResponse, ContractError> {
+        let guests = guests.unwrap_or(1);
+        if guests == 0 {
+            return Err(ContractError::InvalidInput {});
+        }
+        if let Some(property) = token.extension.property() {
+            if guests > property.max_guests {
+                return Err(ContractError::TooManyGuests {
+                    max_guests: property.max_guests,
+                });
+            }
+        }
+
+        let rental = &token.shortterm_rental;
+        let nights = count_nights(check_in, check_out);
+        if nights == 0 {
+            return Err(ContractError::InvalidInput {});
+        }
+        let extra_guests = guests.saturating_sub(rental.base_guests);
+        let extra_guest_fee = Uint128::from(rental.extra_guest_fee)
+            * Uint128::from(extra_guests)
+            * Uint128::from(nights);
+        let rent = Uint128::from(rental.price_per_day) * Uint128::from(nights) + extra_guest_fee;
+
+        if let Some(id) = add_ons
+            .iter()
+            .find(|id| !rental.add_ons.iter().any(|add_on| &add_on.id == *id))
+        {
+            return Err(ContractError::UnknownAddOn { id: id.clone() });
+        }
+        let booked_add_ons: Vec<_> = rental
+            .add_ons
+            .iter()
+            .filter(|add_on| add_on.mandatory || add_ons.contains(&add_on.id))
+            .map(|add_on| BookedAddOn {
+                id: add_on.id.clone(),
+                amount: match add_on.pricing {
+                    AddOnPricing::Flat => Uint128::from(add_on.price),
+                    AddOnPricing::PerNight => Uint128::from(add_on.price) * Uint128::from(nights),
+                },
+                refund: add_on.refund.clone(),
+            })
+            .collect();
+        let deposit = booked_add_ons
+            .iter()
+            .fold(rent, |total, add_on| total + add_on.amount);
+
+        let fee = deposit.multiply_ratio(self.get_fee(storage)?, 10000u128);
+        Ok(ReservationQuoteResponse {
+            denom: rental.denom.clone(),
+            nights,
+            guests,
+            rent,
+            extra_guest_fee,
+            add_ons: booked_add_ons,
+            discount: Uint128::zero(),
+            deposit,
+            fee,
+            total: deposit + fee,
+        })
+    }
+
+    /// Takes a voucher's discount off the rent of `quote` and recomputes the fee on the
+    /// discounted amount
+    pub fn apply_voucher(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        token_id: &str,
+        token: &TokenInfo<T>,
+        code: &str,
+        quote: &mut ReservationQuoteResponse,
+    ) -> Result<Voucher, ContractError> {
+        // the host's own code wins over one of the contract owner
+        let mut issuers = vec![token.owner.clone()];
+        issuers.extend(cw_ownable::get_ownership(storage)?.owner);
+        let voucher = issuers
+            .iter()
+            .find_map(|issuer| self.vouchers.may_load(storage, (issuer, code)).transpose())
+            .transpose()?
+            .ok_or(ContractError::VoucherNotApplicable {})?;
+        let discount =
+            voucher.discount(&env.block, token_id, &token.owner, &quote.denom, quote.rent)?;
+        quote.discount = discount;
+        quote.deposit -= discount;
+        quote.fee = quote
+            .deposit
+            .multiply_ratio(self.get_fee(storage)?, 10000u128);
+        quote.total = quote.deposit + quote.fee;
+        Ok(voucher)
+    }
+
+    /// A stay that is over, either finalized or approved and past checkout. Bookings can
+    /// only start in the future, so the stay was booked before it happened.
+    pub fn reviewable_stay(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        token_id: &str,
+        traveler: &Addr,
+        check_in: u64,
+        check_out: u64,
+    ) -> Result<CompletedStay, ContractError> {
+        if let Some(stay) = self
+            .completed_stays
+            .may_load(storage, (token_id, traveler, check_in))?
+        {
+            if stay.check_out != check_out {
+                return Err(ContractError::NotReserved {});
+            }
+            return Ok(stay);
+        }
+        let token = self.tokens.load(storage, token_id)?;
+        let traveler = token
+            .shortterm_rental
+            .travelers
+            .iter()
+            .find(|item| {
+                item.address.as_ref() == Some(traveler)
+                    && item.renting_period == [check_in, check_out]
+                    && item.approved
+                    && !item.cancelled
+            })
+            .ok_or(ContractError::NotReserved {})?;
+        if traveler.renting_period[1] > env.block.time.seconds() {
+            return Err(ContractError::ReviewNotOpen {});
+        }
+        Ok(CompletedStay {
+            host: traveler.host.clone().unwrap_or(token.owner),
+            check_out,
+        })
+    }
+
+    /// Checks a stay against the listing's rules and existing bookings, returning
+    /// where it goes in the check-in ordered list of travelers
+    pub fn check_stay_rules(
+        &self,
+        env: &Env,
+        rental: &ShortTermRental,
+        check_in: u64,
+        check_out: u64,
+    ) -> Result<usize, ContractError> {
+        let nights = count_nights(check_in, check_out);
+        if nights < rental.minimum_stay {
+            return Err(ContractError::LessThanMinimum {});
+        }
+        if rental.maximum_stay != 0 && nights > rental.maximum_stay {
+            return Err(ContractError::MoreThanMaximum {});
+        }
+        // stays that already started are never bookable, whatever the notice
+        let current_time = env.block.time.seconds();
+        if check_in < current_time.saturating_add(rental.advance_notice) {
+            return Err(ContractError::InsufficientNotice {});
+        }
+        if rental.booking_horizon != 0
+            && check_in > current_time.saturating_add(rental.booking_horizon.saturating_mul(86400))
+        {
+            return Err(ContractError::BeyondBookingHorizon {});
+        }
+
+        // stays may not touch, and keep the cleaning buffer on both sides
+        let buffer = rental.buffer_days.saturating_mul(86400);
+        let overlaps = rental.travelers.iter().any(|traveler| {
+            check_in <= traveler.renting_period[1].saturating_add(buffer)
+                && traveler.renting_period[0] <= check_out.saturating_add(buffer)
+        });
+        if overlaps {
+            return Err(ContractError::UnavailablePeriod {});
+        }
+        Ok(rental
+            .travelers
+            .iter()
+            .position(|traveler| traveler.renting_period[0] > check_in)
+            .unwrap_or(rental.travelers.len()))
+    }
+
     pub fn check_can_edit(
         &self,
         // _deps:Deps,
@@ -1482,3 +4465,67 @@ where
         }
     }
 }
+
+fn validate_payout(api: &dyn Api, payout: PayoutMsg) -> Result<PayoutConfig, ContractError> {
+    let mut shares: Vec<PayoutShare> = vec![];
+    for share in payout.shares {
+        let address = api.addr_validate(&share.address)?;
+        if share.percentage == 0
+            || share.percentage > 100
+            || shares.iter().any(|s| s.address == address)
+        {
+            return Err(ContractError::InvalidPayout {});
+        }
+        shares.push(PayoutShare {
+            address,
+            percentage: share.percentage,
+        });
+    }
+    if shares.iter().map(|s| s.percentage).sum::<u64>() != 100 {
+        return Err(ContractError::InvalidPayout {});
+    }
+    Ok(PayoutConfig {
+        shares,
+        hold: payout.hold,
+    })
+}
+
+/// Check-in and check-out timestamps of a renting period given as strings
+pub fn parse_renting_period(renting_period: &[String]) -> Result<(u64, u64), ContractError> {
+    match renting_period {
+        [check_in, check_out] => match (check_in.parse::<u64>(), check_out.parse::<u64>()) {
+            (Ok(check_in), Ok(check_out)) if check_in < check_out => Ok((check_in, check_out)),
+            (Ok(_), Ok(_)) => Err(ContractError::InvalidInput {}),
+            _ => Err(ContractError::NotReserved {}),
+        },
+        _ => Err(ContractError::NotReserved {}),
+    }
+}
+
+/// Nights a stay is charged and checked against the stay rules for, a started night counts
+/// in full
+fn count_nights(check_in: u64, check_out: u64) -> u64 {
+    check_out.saturating_sub(check_in).saturating_add(86399) / 86400
+}
+
+fn check_batch_size(len: usize) -> Result<(), ContractError> {
+    if len == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+    if len > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE,
+        });
+    }
+    Ok(())
+}
+
+fn validate_royalty(api: &dyn Api, royalty: RoyaltyMsg) -> Result<Royalty, ContractError> {
+    if royalty.share > 10000 {
+        return Err(ContractError::InvalidRoyalty {});
+    }
+    Ok(Royalty {
+        payment_address: api.addr_validate(&royalty.payment_address)?,
+        share: royalty.share,
+    })
+}
@@ -2,8 +2,10 @@ mod error;
 mod execute;
 pub mod helpers;
 pub mod msg;
+pub mod property;
 mod query;
 pub mod state;
+pub mod upgrades;
 
 #[cfg(test)]
 mod contract_tests;
@@ -12,7 +14,8 @@ mod multi_tests;
 
 use cosmwasm_schema::cw_serde;
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, QueryMsg};
+pub use crate::property::{PropertyExtension, PropertyMetadata};
 pub use crate::state::Cw721Contract;
 
 // These types are re-exported so that contracts interacting with this
@@ -40,6 +43,8 @@ pub struct Metadata {
     pub image: Option<Vec<Trait>>,    
     pub attributes: Option<Vec<Trait>>,
     pub external_url: Option<String>,
+    /// Typed description of the property, validated on mint
+    pub property: Option<PropertyMetadata>,
 }
 
 pub type Extension = Option<Metadata>;
@@ -51,18 +56,20 @@ pub type Extension = Option<Metadata>;
 pub const CONTRACT_NAME: &str = "crates.io:codedestate";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-// currently we only support migrating from 0.16.0. this is ok for now because
-// we have not released any 0.16.x where x != 0
-//
-// TODO: parse semvar so that any version 0.16.x can be migrated from
+// Oldest version that can be migrated from, any later version up to
+// CONTRACT_VERSION is accepted
 pub const EXPECTED_FROM_VERSION: &str = "0.16.0";
 
+// Name cw721-base stored before this contract got its own
+pub const LEGACY_CONTRACT_NAME: &str = "crates.io:cw721-base";
+
 pub mod entry {
     use super::*;
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
     use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use semver::Version;
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -95,14 +102,63 @@ pub mod entry {
         tract.query(deps, env, msg)
     }
 
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(
+        mut deps: DepsMut,
+        _env: Env,
+        _msg: MigrateMsg,
+    ) -> Result<Response, ContractError> {
+        let stored = cw2::get_contract_version(deps.storage)?;
+        if stored.contract != CONTRACT_NAME && stored.contract != LEGACY_CONTRACT_NAME {
+            return Err(ContractError::WrongMigrationContract {
+                contract: stored.contract,
+            });
+        }
+
+        let from = parse_version(&stored.version)?;
+        let to = parse_version(CONTRACT_VERSION)?;
+        if from > to {
+            return Err(ContractError::CannotDowngrade {
+                from: stored.version,
+                to: CONTRACT_VERSION.to_string(),
+            });
+        }
+        if from < parse_version(EXPECTED_FROM_VERSION)? {
+            return Err(ContractError::UnsupportedMigrationVersion {
+                version: stored.version,
+            });
+        }
+
+        let applied = upgrades::migrate::<Extension, Empty, Empty, Empty>(deps.branch(), &from)?;
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("from_version", stored.version)
+            .add_attribute("to_version", CONTRACT_VERSION)
+            .add_attributes(applied))
+    }
+
+    fn parse_version(version: &str) -> Result<Version, ContractError> {
+        version
+            .parse()
+            .map_err(|_| ContractError::UnsupportedMigrationVersion {
+                version: version.to_string(),
+            })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Addr, Uint128};
     use cw2::ContractVersion;
+    use cw721::{TokensResponse, Traveler};
+    use cw_storage_plus::{Item, Map};
 
     use super::*;
+    use crate::property::{PropertyAddress, PropertyType};
+    use crate::state::{Approval, TokenInfo};
 
     /// Make sure cw2 version info is properly initialized during instantiation.
     #[test]
@@ -130,4 +186,169 @@ mod tests {
             },
         );
     }
+
+    /// Token as cw721-base 0.16 stored it, before rentals were added
+    #[cw_serde]
+    struct LegacyToken {
+        owner: Addr,
+        approvals: Vec<Approval>,
+        token_uri: Option<String>,
+        extension: Extension,
+    }
+
+    fn legacy_property() -> PropertyMetadata {
+        PropertyMetadata {
+            address: PropertyAddress {
+                street: String::from("1 Marina Walk"),
+                city: String::from("Dubai"),
+                region: None,
+                postal_code: None,
+                country: String::from("AE"),
+            },
+            coordinates: None,
+            property_type: PropertyType::Apartment,
+            bedrooms: 2,
+            bathrooms: 1,
+            max_guests: 4,
+            amenities: vec![],
+            house_rules: vec![],
+            check_in_time: String::from("15:00"),
+            check_out_time: String::from("11:00"),
+            photos: vec![],
+        }
+    }
+
+    #[test]
+    fn migrate_from_016_layout() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, LEGACY_CONTRACT_NAME, "0.16.0").unwrap();
+        let minter = Item::<Addr>::new("minter");
+        minter
+            .save(deps.as_mut().storage, &Addr::unchecked("larry"))
+            .unwrap();
+        Map::<&str, LegacyToken>::new("tokens")
+            .save(
+                deps.as_mut().storage,
+                "marina",
+                &LegacyToken {
+                    owner: Addr::unchecked("larry"),
+                    approvals: vec![],
+                    token_uri: None,
+                    extension: Some(Metadata {
+                        property: Some(legacy_property()),
+                        ..Metadata::default()
+                    }),
+                },
+            )
+            .unwrap();
+
+        let res = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "from_version" && attr.value == "0.16.0"));
+
+        let ownership = cw_ownable::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked("larry")));
+        assert_eq!(minter.may_load(deps.as_ref().storage).unwrap(), None);
+
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        let token = tract.tokens.load(deps.as_ref().storage, "marina").unwrap();
+        assert_eq!(token.owner, Addr::unchecked("larry"));
+        assert_eq!(token.shortterm_rental.denom, "unibi");
+
+        let search = QueryMsg::SearchProperties {
+            city: String::from("dubai"),
+            property_type: None,
+            min_guests: None,
+            min_bedrooms: None,
+            start_after: None,
+            limit: None,
+        };
+        let found: TokensResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), search).unwrap()).unwrap();
+        assert_eq!(found.tokens, vec![String::from("marina")]);
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // migrating the same version again changes nothing
+        entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let again = tract.tokens.load(deps.as_ref().storage, "marina").unwrap();
+        assert_eq!(again, token);
+    }
+
+    #[test]
+    fn migrate_from_018_records_stay_hosts() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.18.0").unwrap();
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        let mut token = TokenInfo::new(Addr::unchecked("larry"), None, None);
+        token.shortterm_rental.travelers.push(Traveler {
+            deposit_amount: Uint128::new(100),
+            approved: true,
+            cancelled: false,
+            renting_period: vec![1_000, 87_400],
+            address: Some(Addr::unchecked("guest")),
+            settled_until: 0,
+            guests: 1,
+            add_ons: vec![],
+            host: None,
+        });
+        tract
+            .tokens
+            .save(deps.as_mut().storage, "marina", &token)
+            .unwrap();
+
+        let res = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let migrations: Vec<_> = res
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "migration")
+            .map(|attr| attr.value.as_str())
+            .collect();
+        assert_eq!(migrations, vec!["0.19.0 stay hosts"]);
+
+        let token = tract.tokens.load(deps.as_ref().storage, "marina").unwrap();
+        assert_eq!(
+            token.shortterm_rental.travelers[0].host,
+            Some(Addr::unchecked("larry"))
+        );
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn migrate_rejects_unknown_versions() {
+        let mut deps = mock_dependencies();
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotDowngrade {
+                from: String::from("99.0.0"),
+                to: CONTRACT_VERSION.to_string(),
+            }
+        );
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.15.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnsupportedMigrationVersion {
+                version: String::from("0.15.0"),
+            }
+        );
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.16.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongMigrationContract {
+                contract: String::from("crates.io:cw20-base"),
+            }
+        );
+    }
 }
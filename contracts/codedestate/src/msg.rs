@@ -1,11 +1,30 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Binary;
 use cosmwasm_std::Coin;
+use cosmwasm_std::Decimal;
+use cosmwasm_std::Event;
+use cosmwasm_std::Uint128;
+use cw721::AuctionKind;
 use cw721::CancellationItem;
 use cw721::Expiration;
+use cw721::{AddOn, BookedAddOn};
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
 use schemars::JsonSchema;
 
+use crate::property::{PropertyType, PropertyUpdate};
+use crate::state::{
+    Fraction, GuestAttestation, ListingTemplate, MetadataVersion, MintPolicy, Offer, PayoutConfig,
+    Royalty, SaleListing, Shareholder, TokenRole, TreasuryConfig, Verification, Voucher,
+    VoucherDiscount, WithdrawalProposal,
+};
+
+/// Most items a single batch message may carry
+pub const MAX_BATCH_SIZE: usize = 50;
+/// Most add-ons a short term listing can offer
+pub const MAX_ADD_ONS: usize = 20;
+/// Seconds after checkout during which a stay can be reviewed
+pub const REVIEW_WINDOW: u64 = 14 * 86400;
+
 #[cw_serde]
 pub struct InstantiateMsg {
     /// Name of the NFT contract
@@ -19,6 +38,10 @@ pub struct InstantiateMsg {
     pub minter: String,
 }
 
+/// Data migrations are picked from the stored cw2 version, so there is nothing to pass
+#[cw_serde]
+pub struct MigrateMsg {}
+
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
 /// to make this stand-alone. You will likely want to remove mint and
 /// use other control logic in any contract that inherits this.
@@ -64,7 +87,216 @@ pub enum ExecuteMsg<T, E> {
         target: String,
         amount: Coin,
     },
-    /// Mint a new NFT, can only be called by the contract minter
+    /// Configure the timelock and signer set guarding treasury withdrawals.
+    /// Only the contract owner can call this
+    UpdateTreasuryConfig {
+        timelock: u64,
+        instant_limits: Vec<Coin>,
+        signers: Vec<String>,
+        threshold: u32,
+    },
+    /// Queue a withdrawal that can be executed once the timelock has passed
+    /// and enough signers approved it
+    ProposeWithdrawal {
+        target: String,
+        amount: Coin,
+    },
+    ApproveWithdrawal {
+        id: u64,
+    },
+    ExecuteWithdrawal {
+        id: u64,
+    },
+    CancelWithdrawal {
+        id: u64,
+    },
+    /// Set where the rental proceeds of a token are paid.
+    /// `None` falls back to the host default
+    SetTokenPayout {
+        token_id: String,
+        payout: Option<PayoutMsg>,
+    },
+    /// Set the default payout for all tokens of the sender.
+    /// `None` pays the owner directly
+    SetHostPayout {
+        payout: Option<PayoutMsg>,
+    },
+    /// Send the payouts held for the sender in `denom`
+    ClaimPayout {
+        denom: String,
+    },
+    /// Allow `grantee` to perform the actions of `role` on a token without
+    /// giving it transfer rights. Replaces an existing grant of the same role
+    GrantRole {
+        token_id: String,
+        grantee: String,
+        role: TokenRole,
+        expires: Option<Expiration>,
+    },
+    RevokeRole {
+        token_id: String,
+        grantee: String,
+        role: TokenRole,
+    },
+    /// Change who can mint. Only the contract owner can call this
+    UpdateMintPolicy {
+        policy: MintPolicy,
+    },
+    /// Add or remove issuers allowed to mint under the allowlist policy.
+    /// Only the contract owner can call this
+    UpdateMinters {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Transfer a token together with its reservations. Rent the bookings earned so far
+    /// is paid out to the current owner, the recipient takes over the rest
+    TransferNftWithReservations {
+        recipient: String,
+        token_id: String,
+    },
+    /// Send a token together with its reservations to a contract and trigger an action
+    /// on the receiving contract, settling earned rent like `TransferNftWithReservations`
+    SendNftWithReservations {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    /// Refund every traveler and tenant of a token, then burn it.
+    /// Only the contract owner can call this
+    EmergencyBurn {
+        token_id: String,
+    },
+    /// Offer a token for sale at a fixed price. Bookings move along with the token
+    /// and the rent they earned so far is settled to the seller at the sale
+    ListForSale {
+        token_id: String,
+        price: Coin,
+    },
+    DelistFromSale {
+        token_id: String,
+    },
+    /// Buy a listed token, sending exactly the listed price
+    Buy {
+        token_id: String,
+    },
+    /// Offer the attached funds for a token. They are held until the offer is
+    /// accepted or cancelled, a new offer replaces the previous one of the sender
+    MakeOffer {
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    /// Sell the token to `buyer` for its offer
+    AcceptOffer {
+        token_id: String,
+        buyer: String,
+    },
+    /// Withdraw the sender's offer and get the funds back
+    CancelOffer {
+        token_id: String,
+    },
+    /// Lock a token into `shares` shares held by the owner. Rent paid in the
+    /// denom of `buyout_price` is then distributed to the shareholders
+    Fractionalize {
+        token_id: String,
+        shares: Uint128,
+        buyout_price: Coin,
+    },
+    TransferShares {
+        token_id: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    ClaimDividends {
+        token_id: String,
+    },
+    /// Buy a fractionalized token by sending exactly its buyout price. The
+    /// payment is redeemable by the shareholders, the buyer's own shares included
+    Buyout {
+        token_id: String,
+    },
+    /// Redeem all shares of a bought out token for their part of the buyout price
+    RedeemShares {
+        token_id: String,
+    },
+    /// Auction a token for `duration` seconds starting at `price` in `denom`.
+    /// The token cannot be transferred until the auction is settled or cancelled
+    StartAuction {
+        token_id: String,
+        kind: AuctionKind,
+        denom: String,
+        price: u64,
+        duration: u64,
+    },
+    /// Bid the attached funds. An outbid bidder is refunded right away,
+    /// a bid reaching the current price of a Dutch auction buys the token
+    PlaceBid {
+        token_id: String,
+    },
+    /// Close an ended English auction, selling to the highest bidder if the
+    /// reserve price was met and refunding it otherwise
+    SettleAuction {
+        token_id: String,
+    },
+    /// Stop an auction that has no bids yet
+    CancelAuction {
+        token_id: String,
+    },
+    /// Set the royalty for tokens without their own, `None` removes it.
+    /// Only the contract owner can call this
+    SetDefaultRoyalty {
+        royalty: Option<RoyaltyMsg>,
+    },
+    /// Set the royalty of a single token, `None` falls back to the default.
+    /// Only the contract owner can call this
+    SetTokenRoyalty {
+        token_id: String,
+        royalty: Option<RoyaltyMsg>,
+    },
+    /// Appoint or dismiss property verifiers. Only the contract owner can call this
+    UpdateVerifiers {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Require tokens to be verified before listing. Only the contract owner can call this
+    SetRequireVerification {
+        required: bool,
+    },
+    /// Mark a token as a verified property, can only be called by a verifier
+    VerifyProperty {
+        token_id: String,
+        attestation_uri: String,
+    },
+    /// Withdraw a verification, callable by any verifier or the contract owner
+    RevokeVerification {
+        token_id: String,
+    },
+    /// Appoint or dismiss guest KYC providers. Only the contract owner can call this.
+    /// Attestations of a dismissed provider stop counting for bookings.
+    UpdateKycProviders {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Mark an address as identity checked, can only be called by a KYC provider
+    AttestGuest {
+        address: String,
+        level: u8,
+        expires: Expiration,
+    },
+    /// Withdraw an attestation, callable by any KYC provider or the contract owner
+    RevokeAttestation {
+        address: String,
+    },
+    /// Bar addresses from booking any property. Only the contract owner can call this
+    UpdateGlobalBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Bar addresses from booking the sender's properties
+    UpdateHostBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Mint a new NFT, allowed senders depend on the mint policy
     Mint {
         /// Unique ID of the NFT
         token_id: String,
@@ -77,6 +309,55 @@ pub enum ExecuteMsg<T, E> {
         /// Any custom extension used by this contract
         extension: T,
     },
+    /// Mint up to `MAX_BATCH_SIZE` tokens at once, all or none are minted.
+    /// An open mint fee is due for each of them
+    BatchMint {
+        mints: Vec<MintMsg<T>>,
+    },
+    /// Transfer up to `MAX_BATCH_SIZE` tokens to one recipient, all or none move
+    BatchTransfer {
+        recipient: String,
+        token_ids: Vec<String>,
+    },
+    /// List up to `MAX_BATCH_SIZE` tokens for short term rental, all or none are listed
+    BatchSetListing {
+        listings: Vec<ShortTermListingMsg>,
+    },
+    /// Create or change one of the sender's listing templates. With `propagate`, linked
+    /// listings without reservations are updated too, the others keep their settings
+    SaveListingTemplate {
+        template_id: String,
+        template: ListingTemplate,
+        propagate: bool,
+    },
+    /// Delete a listing template, its listings stay as they are
+    RemoveListingTemplate {
+        template_id: String,
+    },
+    /// Create a promo code. Hosts can only issue codes for their own tokens, the contract
+    /// owner for any. Codes are per issuer, so different issuers can use the same code.
+    CreateVoucher {
+        code: String,
+        discount: VoucherDiscount,
+        max_uses: Option<u32>,
+        expires: Option<Expiration>,
+        #[serde(default)]
+        token_ids: Vec<String>,
+        #[serde(default)]
+        hosts: Vec<String>,
+    },
+    /// Delete a promo code, by its issuer or the contract owner
+    RemoveVoucher {
+        code: String,
+        /// Defaults to the sender
+        issuer: Option<String>,
+    },
+    /// List up to `MAX_BATCH_SIZE` tokens for short term rental with one of the sender's
+    /// templates and link them to it. Listing a token by hand removes the link
+    ApplyListingTemplate {
+        template_id: String,
+        token_ids: Vec<String>,
+    },
 
     SetFeeValue {
         fee: u64,
@@ -86,6 +367,16 @@ pub enum ExecuteMsg<T, E> {
         token_id: String,
         token_uri: String,
     },
+    /// Replace the on-chain extension of a token
+    UpdateExtension {
+        token_id: String,
+        extension: T,
+    },
+    /// Change only the given fields of the token's property metadata
+    UpdateProperty {
+        token_id: String,
+        update: PropertyUpdate,
+    },
 
     // Short term rental
     SetListForShortTermRental {
@@ -100,9 +391,45 @@ pub enum ExecuteMsg<T, E> {
     SetUnlistForShorttermRental {
         token_id: String,
     },
+    /// Limits on the stays of a short term listing, 0 turns a rule off.
+    /// Only for listed tokens, unlisting clears the rules.
+    SetStayRules {
+        token_id: String,
+        /// Days
+        maximum_stay: u64,
+        /// Days
+        booking_horizon: u64,
+        /// Seconds
+        advance_notice: u64,
+        /// Days
+        buffer_days: u64,
+    },
+    /// Attestation level guests need to book a short term stay, 0 for none
+    SetGuestKycLevel {
+        token_id: String,
+        min_level: u8,
+    },
+    /// Per night fee for guests above the occupancy included in the base price
+    SetGuestPricing {
+        token_id: String,
+        base_guests: u32,
+        extra_guest_fee: u64,
+    },
+    /// Replaces the add-ons offered with a short term listing
+    SetAddOns {
+        token_id: String,
+        add_ons: Vec<AddOn>,
+    },
     SetReservationForShortTerm {
         token_id: String,
         renting_period: Vec<String>,
+        /// Defaults to a single guest
+        guests: Option<u32>,
+        /// Ids of the optional add-ons wanted, mandatory ones are always charged
+        #[serde(default)]
+        add_ons: Vec<String>,
+        /// Promo code of the host, or failing that of the contract owner
+        voucher: Option<String>,
     },
     CancelReservationForShortterm {
         token_id: String,
@@ -134,6 +461,15 @@ pub enum ExecuteMsg<T, E> {
         traveler: String,
         renting_period: Vec<String>,
     },
+    /// Rate the other party of a stay from 1 to 5, once per party within `REVIEW_WINDOW`
+    /// after checkout
+    ReviewStay {
+        token_id: String,
+        traveler: String,
+        renting_period: Vec<String>,
+        rating: u8,
+        comment_uri: Option<String>,
+    },
 
     //Long term rental
     // SetListForLongTermRental {
@@ -247,6 +583,159 @@ pub enum QueryMsg<Q: JsonSchema> {
 
     #[returns(u64)]
     GetBalance { denom: String },
+
+    /// Cumulative deposited and withdrawn amounts of the fee treasury, per denom
+    #[returns(TreasuryTotalsResponse)]
+    TreasuryTotals {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Option<TreasuryConfig>)]
+    TreasuryConfig {},
+
+    #[returns(WithdrawalProposal)]
+    Withdrawal { id: u64 },
+
+    /// Lists queued and past withdrawals ordered by id
+    #[returns(WithdrawalsResponse)]
+    Withdrawals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Where the proceeds of a token are paid after resolving token and host defaults
+    #[returns(PayoutConfig)]
+    Payout { token_id: String },
+
+    #[returns(Option<PayoutConfig>)]
+    HostPayout { host: String },
+
+    /// Payouts held for `address` that can be claimed
+    #[returns(ClaimableResponse)]
+    Claimable { address: String },
+    #[returns(Option<Fraction>)]
+    Fraction { token_id: String },
+
+    #[returns(Option<crate::PropertyMetadata>)]
+    Property { token_id: String },
+
+    /// Whether `renting_period` could be reserved now, with the rule it breaks if not
+    #[returns(AvailabilityResponse)]
+    CheckAvailability {
+        token_id: String,
+        renting_period: Vec<String>,
+    },
+
+    /// What a reservation of `renting_period` costs, to be sent with `SetReservationForShortTerm`
+    #[returns(ReservationQuoteResponse)]
+    QuoteReservation {
+        token_id: String,
+        renting_period: Vec<String>,
+        guests: Option<u32>,
+        #[serde(default)]
+        add_ons: Vec<String>,
+        voucher: Option<String>,
+    },
+
+    #[returns(Option<Voucher>)]
+    Voucher { issuer: String, code: String },
+
+    /// Ratings travelers gave a token
+    #[returns(RatingResponse)]
+    TokenRating { token_id: String },
+
+    /// Ratings a host or traveler received
+    #[returns(RatingResponse)]
+    AddressRating { address: String },
+
+    #[returns(ListingTemplatesResponse)]
+    ListingTemplates { host: String },
+
+    /// Provenance log of a token: mints, transfers, metadata and listing changes,
+    /// completed stays and burns, oldest first
+    #[returns(TokenHistoryResponse)]
+    TokenHistory {
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Past and current metadata of a token, oldest first
+    #[returns(MetadataHistoryResponse<Q>)]
+    MetadataHistory {
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Tokens whose property is in `city`, optionally narrowed down by type and capacity
+    #[returns(cw721::TokensResponse)]
+    SearchProperties {
+        city: String,
+        property_type: Option<PropertyType>,
+        min_guests: Option<u32>,
+        min_bedrooms: Option<u32>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Shareholder)]
+    Shareholder { token_id: String, holder: String },
+
+    #[returns(cw721::AuctionInfoResponse)]
+    Auction { token_id: String },
+
+    #[returns(Option<SaleListing>)]
+    SaleListing { token_id: String },
+
+    /// Open offers on a token ordered by buyer
+    #[returns(OffersResponse)]
+    Offers {
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// CW2981 royalty owed on a sale of the token at `sale_price`
+    #[returns(cw721::RoyaltiesInfoResponse)]
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+
+    #[returns(cw721::CheckRoyaltiesResponse)]
+    CheckRoyalties {},
+
+    #[returns(Option<Royalty>)]
+    DefaultRoyalty {},
+
+    #[returns(Option<Verification>)]
+    Verification { token_id: String },
+
+    #[returns(VerifiersResponse)]
+    Verifiers {},
+
+    #[returns(Option<GuestAttestation>)]
+    Attestation { address: String },
+
+    #[returns(KycProvidersResponse)]
+    KycProviders {},
+
+    /// Addresses blocked by `host`, or contract wide when `None`
+    #[returns(BlocklistResponse)]
+    Blocklist {
+        host: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Roles granted on a token
+    #[returns(TokenRolesResponse)]
+    TokenRoles {
+        token_id: String,
+        /// unset or false will filter out expired grants, you must set to true to see them
+        include_expired: Option<bool>,
+    },
     /// With MetaData Extension.
     /// Returns top-level metadata about the contract
     #[returns(cw721::ContractInfoResponse)]
@@ -299,8 +788,167 @@ pub enum QueryMsg<Q: JsonSchema> {
     Extension { msg: Q },
 }
 
+#[cw_serde]
+pub struct AvailabilityResponse {
+    pub available: bool,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct ReservationQuoteResponse {
+    pub denom: String,
+    pub nights: u64,
+    pub guests: u32,
+    /// Nightly rent, extra guest fees included
+    pub rent: Uint128,
+    pub extra_guest_fee: Uint128,
+    pub add_ons: Vec<BookedAddOn>,
+    /// Taken off the rent by a voucher
+    pub discount: Uint128,
+    /// Rent and add-ons after the discount, held in escrow for the host
+    pub deposit: Uint128,
+    pub fee: Uint128,
+    pub total: Uint128,
+}
+
+#[cw_serde]
+pub struct RatingResponse {
+    pub count: u64,
+    pub average: Decimal,
+}
+
+#[cw_serde]
+pub struct ListingTemplateInfo {
+    pub template_id: String,
+    pub template: ListingTemplate,
+    /// Tokens currently listed with this template
+    pub token_ids: Vec<String>,
+}
+
+#[cw_serde]
+pub struct ListingTemplatesResponse {
+    pub templates: Vec<ListingTemplateInfo>,
+}
+
+#[cw_serde]
+pub struct ProvenanceEntry {
+    pub seq: u64,
+    pub event: Event,
+}
+
+#[cw_serde]
+pub struct TokenHistoryResponse {
+    pub entries: Vec<ProvenanceEntry>,
+}
+
+#[cw_serde]
+pub struct MintMsg<T> {
+    pub token_id: String,
+    pub owner: String,
+    pub token_uri: Option<String>,
+    pub extension: T,
+}
+
+#[cw_serde]
+pub struct ShortTermListingMsg {
+    pub token_id: String,
+    pub denom: String,
+    pub price_per_day: u64,
+    pub auto_approve: bool,
+    pub available_period: Vec<String>,
+    pub minimum_stay: u64,
+    pub cancellation: Vec<CancellationItem>,
+}
+
+#[cw_serde]
+pub struct MetadataHistoryResponse<T> {
+    pub versions: Vec<MetadataVersion<T>>,
+}
+
+#[cw_serde]
+pub struct OffersResponse {
+    pub offers: Vec<Offer>,
+}
+
+#[cw_serde]
+pub struct RoyaltyMsg {
+    pub payment_address: String,
+    /// Share of the sale price in basis points (/10000)
+    pub share: u64,
+}
+
+#[cw_serde]
+pub struct VerifiersResponse {
+    pub verifiers: Vec<String>,
+    /// Whether listing requires a verification
+    pub required: bool,
+}
+
+#[cw_serde]
+pub struct KycProvidersResponse {
+    pub providers: Vec<String>,
+}
+
+#[cw_serde]
+pub struct BlocklistResponse {
+    pub addresses: Vec<String>,
+}
+
+#[cw_serde]
+pub struct RoleAssignment {
+    pub grantee: String,
+    pub role: TokenRole,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct TokenRolesResponse {
+    pub roles: Vec<RoleAssignment>,
+}
+
+#[cw_serde]
+pub struct PayoutShareMsg {
+    pub address: String,
+    pub percentage: u64,
+}
+
+#[cw_serde]
+pub struct PayoutMsg {
+    /// Percentages of every payout, must add up to 100
+    pub shares: Vec<PayoutShareMsg>,
+    /// Keep the shares in the contract until claimed with `ClaimPayout`
+    pub hold: bool,
+}
+
+#[cw_serde]
+pub struct ClaimableResponse {
+    pub amounts: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct DenomTotals {
+    pub denom: String,
+    /// Amount currently held by the treasury
+    pub balance: Uint128,
+    pub deposited: Uint128,
+    pub withdrawn: Uint128,
+}
+
+#[cw_serde]
+pub struct TreasuryTotalsResponse {
+    pub totals: Vec<DenomTotals>,
+}
+
+#[cw_serde]
+pub struct WithdrawalsResponse {
+    pub withdrawals: Vec<WithdrawalProposal>,
+}
+
 /// Shows who can mint these tokens
 #[cw_serde]
 pub struct MinterResponse {
     pub minter: Option<String>,
+    pub policy: MintPolicy,
+    /// Issuers allowed to mint under `MintPolicy::Allowlist`
+    pub allowed_minters: Vec<String>,
 }
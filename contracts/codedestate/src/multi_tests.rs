@@ -1,8 +1,8 @@
 use cosmwasm_std::{to_binary, Addr, Empty, QuerierWrapper, WasmMsg};
-use cw721::OwnerOfResponse;
+use cw721::{OwnerOfResponse, ShortTermRental};
 use cw_multi_test::{App, Contract, ContractWrapper, Executor};
 
-use crate::MinterResponse;
+use crate::{MigrateMsg, MinterResponse};
 
 fn cw721_base_contract() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
@@ -78,59 +78,90 @@ fn mint_transfer_and_burn(app: &mut App, cw721: Addr, sender: Addr, token_id: St
 }
 
 /// Instantiates a 0.16 version of this contract and tests that tokens
-/// can be minted, transferred, and burnred after migration.
-
-// #[test]
-// fn test_016_017_migration() {
-//     use cw721_base_016 as v16;
-//     let mut app = App::default();
-//     let admin = || Addr::unchecked("admin");
-
-//     let code_id_016 = app.store_code(cw721_base_016_contract());
-//     let code_id_017 = app.store_code(cw721_base_contract());
-
-//     let cw721 = app
-//         .instantiate_contract(
-//             code_id_016,
-//             admin(),
-//             &v16::InstantiateMsg {
-//                 name: "collection".to_string(),
-//                 symbol: "symbol".to_string(),
-//                 minter: admin().into_string(),
-//             },
-//             &[],
-//             "codedestate",
-//             Some(admin().into_string()),
-//         )
-//         .unwrap();
-
-//     mint_transfer_and_burn(&mut app, cw721.clone(), admin(), "1".to_string());
-
-//     app.execute(
-//         admin(),
-//         WasmMsg::Migrate {
-//             contract_addr: cw721.to_string(),
-//             new_code_id: code_id_017,
-//             msg: to_binary(&Empty::default()).unwrap(),
-//         }
-//         .into(),
-//     )
-//     .unwrap();
-
-//     mint_transfer_and_burn(&mut app, cw721.clone(), admin(), "1".to_string());
-
-//     // check new mint query response works.
-//     let m: MinterResponse = app
-//         .wrap()
-//         .query_wasm_smart(&cw721, &crate::QueryMsg::<Empty>::Minter {})
-//         .unwrap();
-//     assert_eq!(m.minter, Some(admin().to_string()));
-
-//     // check that the new response is backwards compatable when minter
-//     // is not None.
-//     let m: v16::MinterResponse = app
-//         .wrap()
-//         .query_wasm_smart(&cw721, &crate::QueryMsg::<Empty>::Minter {})
-//         .unwrap();
-//     assert_eq!(m.minter, admin().to_string());
-// }
+/// can be minted, transferred, and burnred after migration, and that
+/// tokens minted before it are carried over.
+#[test]
+fn test_016_019_migration() {
+    use cw721_base_016 as v16;
+    let mut app = App::default();
+    let admin = || Addr::unchecked("admin");
+
+    let code_id_016 = app.store_code(cw721_base_016_contract());
+    let code_id_019 = app.store_code(cw721_base_contract());
+
+    let cw721 = app
+        .instantiate_contract(
+            code_id_016,
+            admin(),
+            &v16::InstantiateMsg {
+                name: "collection".to_string(),
+                symbol: "symbol".to_string(),
+                minter: admin().into_string(),
+            },
+            &[],
+            "codedestate",
+            Some(admin().into_string()),
+        )
+        .unwrap();
+
+    mint_transfer_and_burn(&mut app, cw721.clone(), admin(), "1".to_string());
+    app.execute_contract(
+        admin(),
+        cw721.clone(),
+        &crate::ExecuteMsg::<Empty, Empty>::Mint {
+            token_id: "kept".to_string(),
+            owner: admin().into_string(),
+            token_uri: Some("https://example.com/kept".to_string()),
+            extension: Empty::default(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        admin(),
+        WasmMsg::Migrate {
+            contract_addr: cw721.to_string(),
+            new_code_id: code_id_019,
+            msg: to_binary(&MigrateMsg {}).unwrap(),
+        }
+        .into(),
+    )
+    .unwrap();
+
+    mint_transfer_and_burn(&mut app, cw721.clone(), admin(), "1".to_string());
+
+    // check new mint query response works.
+    let m: MinterResponse = app
+        .wrap()
+        .query_wasm_smart(&cw721, &crate::QueryMsg::<Empty>::Minter {})
+        .unwrap();
+    assert_eq!(m.minter, Some(admin().to_string()));
+
+    // the token minted before the migration got the current layout
+    let owner = query_owner(app.wrap(), &cw721, "kept".to_string());
+    assert_eq!(owner, admin());
+    let rental: ShortTermRental = app
+        .wrap()
+        .query_wasm_smart(
+            &cw721,
+            &crate::QueryMsg::<Empty>::NftInfoShortTermRental {
+                token_id: "kept".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(rental.islisted, None);
+    assert_eq!(rental.denom, "unibi");
+
+    // running the same migration again is a no-op
+    app.execute(
+        admin(),
+        WasmMsg::Migrate {
+            contract_addr: cw721.to_string(),
+            new_code_id: code_id_019,
+            msg: to_binary(&MigrateMsg {}).unwrap(),
+        }
+        .into(),
+    )
+    .unwrap();
+}
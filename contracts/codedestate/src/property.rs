@@ -0,0 +1,218 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Empty;
+
+use crate::error::ContractError;
+use crate::Metadata;
+
+const MAX_TEXT_LENGTH: usize = 256;
+const MAX_LIST_LENGTH: usize = 50;
+
+#[cw_serde]
+pub struct PropertyAddress {
+    pub street: String,
+    pub city: String,
+    pub region: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: String,
+}
+
+/// Coordinates in millionths of a degree
+#[cw_serde]
+pub struct GeoCoordinates {
+    pub latitude_e6: i32,
+    pub longitude_e6: i32,
+}
+
+#[cw_serde]
+pub enum PropertyType {
+    Apartment,
+    House,
+    Villa,
+    Cabin,
+    Room,
+    Other,
+}
+
+#[cw_serde]
+pub struct PropertyMetadata {
+    pub address: PropertyAddress,
+    pub coordinates: Option<GeoCoordinates>,
+    pub property_type: PropertyType,
+    pub bedrooms: u32,
+    pub bathrooms: u32,
+    pub max_guests: u32,
+    #[serde(default)]
+    pub amenities: Vec<String>,
+    #[serde(default)]
+    pub house_rules: Vec<String>,
+    /// Local time as "HH:MM"
+    pub check_in_time: String,
+    /// Local time as "HH:MM"
+    pub check_out_time: String,
+    /// Photo URIs, the first one is the cover
+    #[serde(default)]
+    pub photos: Vec<String>,
+}
+
+impl PropertyMetadata {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        check_text("address.street", &self.address.street)?;
+        check_text("address.city", &self.address.city)?;
+        check_text("address.country", &self.address.country)?;
+        if let Some(region) = &self.address.region {
+            check_text("address.region", region)?;
+        }
+        if let Some(postal_code) = &self.address.postal_code {
+            check_text("address.postal_code", postal_code)?;
+        }
+        if let Some(coordinates) = &self.coordinates {
+            if coordinates.latitude_e6.abs() > 90_000_000
+                || coordinates.longitude_e6.abs() > 180_000_000
+            {
+                return Err(invalid("coordinates"));
+            }
+        }
+        if self.max_guests == 0 {
+            return Err(invalid("max_guests"));
+        }
+        check_time("check_in_time", &self.check_in_time)?;
+        check_time("check_out_time", &self.check_out_time)?;
+        check_list("amenities", &self.amenities)?;
+        check_list("house_rules", &self.house_rules)?;
+        check_list("photos", &self.photos)
+    }
+}
+
+/// Partial update of a [`PropertyMetadata`], fields left as `None` are kept
+#[cw_serde]
+#[derive(Default)]
+pub struct PropertyUpdate {
+    pub address: Option<PropertyAddress>,
+    pub coordinates: Option<GeoCoordinates>,
+    pub property_type: Option<PropertyType>,
+    pub bedrooms: Option<u32>,
+    pub bathrooms: Option<u32>,
+    pub max_guests: Option<u32>,
+    pub amenities: Option<Vec<String>>,
+    pub house_rules: Option<Vec<String>>,
+    pub check_in_time: Option<String>,
+    pub check_out_time: Option<String>,
+    pub photos: Option<Vec<String>>,
+}
+
+impl PropertyUpdate {
+    pub fn apply(self, property: &mut PropertyMetadata) {
+        if let Some(address) = self.address {
+            property.address = address;
+        }
+        if let Some(coordinates) = self.coordinates {
+            property.coordinates = Some(coordinates);
+        }
+        if let Some(property_type) = self.property_type {
+            property.property_type = property_type;
+        }
+        if let Some(bedrooms) = self.bedrooms {
+            property.bedrooms = bedrooms;
+        }
+        if let Some(bathrooms) = self.bathrooms {
+            property.bathrooms = bathrooms;
+        }
+        if let Some(max_guests) = self.max_guests {
+            property.max_guests = max_guests;
+        }
+        if let Some(amenities) = self.amenities {
+            property.amenities = amenities;
+        }
+        if let Some(house_rules) = self.house_rules {
+            property.house_rules = house_rules;
+        }
+        if let Some(check_in_time) = self.check_in_time {
+            property.check_in_time = check_in_time;
+        }
+        if let Some(check_out_time) = self.check_out_time {
+            property.check_out_time = check_out_time;
+        }
+        if let Some(photos) = self.photos {
+            property.photos = photos;
+        }
+    }
+}
+
+/// Key of the property search index
+pub fn normalize_city(city: &str) -> String {
+    city.trim().to_lowercase()
+}
+
+/// Token extensions that may carry typed property metadata
+pub trait PropertyExtension {
+    fn property(&self) -> Option<&PropertyMetadata>;
+    fn property_mut(&mut self) -> Option<&mut PropertyMetadata>;
+}
+
+impl PropertyExtension for Option<Metadata> {
+    fn property(&self) -> Option<&PropertyMetadata> {
+        self.as_ref()
+            .and_then(|metadata| metadata.property.as_ref())
+    }
+
+    fn property_mut(&mut self) -> Option<&mut PropertyMetadata> {
+        self.as_mut()
+            .and_then(|metadata| metadata.property.as_mut())
+    }
+}
+
+impl PropertyExtension for Option<Empty> {
+    fn property(&self) -> Option<&PropertyMetadata> {
+        None
+    }
+
+    fn property_mut(&mut self) -> Option<&mut PropertyMetadata> {
+        None
+    }
+}
+
+impl PropertyExtension for Empty {
+    fn property(&self) -> Option<&PropertyMetadata> {
+        None
+    }
+
+    fn property_mut(&mut self) -> Option<&mut PropertyMetadata> {
+        None
+    }
+}
+
+fn invalid(field: &str) -> ContractError {
+    ContractError::InvalidPropertyMetadata {
+        field: field.to_string(),
+    }
+}
+
+fn check_text(field: &str, value: &str) -> Result<(), ContractError> {
+    if value.trim().is_empty() || value.len() > MAX_TEXT_LENGTH {
+        return Err(invalid(field));
+    }
+    Ok(())
+}
+
+fn check_list(field: &str, values: &[String]) -> Result<(), ContractError> {
+    if values.len() > MAX_LIST_LENGTH {
+        return Err(invalid(field));
+    }
+    values.iter().try_for_each(|value| check_text(field, value))
+}
+
+fn check_time(field: &str, value: &str) -> Result<(), ContractError> {
+    let valid = match value.split_once(':') {
+        Some((hours, minutes)) if hours.len() == 2 && minutes.len() == 2 => {
+            matches!(
+                (hours.parse::<u8>(), minutes.parse::<u8>()),
+                (Ok(h), Ok(m)) if h < 24 && m < 60
+            )
+        }
+        _ => false,
+    };
+    if !valid {
+        return Err(invalid(field));
+    }
+    Ok(())
+}
@@ -2,26 +2,36 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use cosmwasm_std::{
-    to_binary, Addr, Binary, BlockInfo, CustomMsg, Deps, Env, Order, StdError, StdResult,
+    to_binary, Addr, Binary, BlockInfo, Coin, CustomMsg, Decimal, Deps, Env, Order, StdError,
+    StdResult, Uint128,
 };
 
 use cw721::{
-    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Cw721Query,
-    Expiration, LongTermRental, NftInfoResponse, NumTokensResponse, OperatorResponse,
-    OperatorsResponse, OwnerOfResponse, ShortTermRental, TokensResponse,
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, AuctionInfoResponse,
+    CheckRoyaltiesResponse, ContractInfoResponse, Cw721Query, Expiration, LongTermRental,
+    NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
+    RoyaltiesInfoResponse, ShortTermRental, TokensResponse,
 };
 use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
 
-use crate::msg::{MinterResponse, QueryMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::execute::parse_renting_period;
+use crate::msg::{
+    AvailabilityResponse, BlocklistResponse, ClaimableResponse, DenomTotals, KycProvidersResponse,
+    ListingTemplateInfo, ListingTemplatesResponse, MetadataHistoryResponse, MinterResponse,
+    OffersResponse, ProvenanceEntry, QueryMsg, RatingResponse, RoleAssignment,
+    TokenHistoryResponse, TokenRolesResponse, TreasuryTotalsResponse, VerifiersResponse,
+    WithdrawalsResponse,
+};
+use crate::property::{normalize_city, PropertyExtension, PropertyType};
+use crate::state::{Approval, Cw721Contract, RatingSummary, TokenInfo};
 
 const DEFAULT_LIMIT: u32 = 4294967295;
 const MAX_LIMIT: u32 = 4294967295;
 
 impl<'a, T, C, E, Q> Cw721Query<T> for Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
@@ -298,11 +308,37 @@ where
             // mode: info.mode,
         })
     }
+
+    fn royalty_info(
+        &self,
+        deps: Deps,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltiesInfoResponse> {
+        // make sure the token exists
+        self.tokens.load(deps.storage, &token_id)?;
+        Ok(match self.royalty(deps.storage, &token_id)? {
+            Some(royalty) => RoyaltiesInfoResponse {
+                royalty_amount: royalty.amount(sale_price),
+                address: royalty.payment_address.into_string(),
+            },
+            None => RoyaltiesInfoResponse {
+                address: String::new(),
+                royalty_amount: Uint128::zero(),
+            },
+        })
+    }
+
+    fn check_royalties(&self, _deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+        Ok(CheckRoyaltiesResponse {
+            royalty_payments: true,
+        })
+    }
 }
 
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
@@ -360,6 +396,151 @@ where
             QueryMsg::NumTokens {} => to_binary(&self.num_tokens(deps)?),
             QueryMsg::GetFee {} => to_binary(&self.get_fee(deps.storage)?),
             QueryMsg::GetBalance { denom } => to_binary(&self.get_balance(deps.storage, denom)?),
+            QueryMsg::TreasuryTotals { start_after, limit } => {
+                to_binary(&self.query_treasury_totals(deps, start_after, limit)?)
+            }
+            QueryMsg::TreasuryConfig {} => to_binary(&self.treasury_config.may_load(deps.storage)?),
+            QueryMsg::Withdrawal { id } => to_binary(&self.withdrawals.load(deps.storage, id)?),
+            QueryMsg::Withdrawals { start_after, limit } => {
+                to_binary(&self.query_withdrawals(deps, start_after, limit)?)
+            }
+            QueryMsg::Payout { token_id } => {
+                let token = self.tokens.load(deps.storage, &token_id)?;
+                to_binary(&self.payout_config(deps.storage, &token_id, &token.owner)?)
+            }
+            QueryMsg::HostPayout { host } => {
+                let host = deps.api.addr_validate(&host)?;
+                to_binary(&self.host_payouts.may_load(deps.storage, &host)?)
+            }
+            QueryMsg::Claimable { address } => to_binary(&self.query_claimable(deps, address)?),
+            QueryMsg::Fraction { token_id } => {
+                to_binary(&self.fractions.may_load(deps.storage, &token_id)?)
+            }
+            QueryMsg::Shareholder { token_id, holder } => {
+                let holder = deps.api.addr_validate(&holder)?;
+                to_binary(&self.shareholder(deps.storage, &token_id, &holder)?)
+            }
+            QueryMsg::Property { token_id } => {
+                let token = self.tokens.load(deps.storage, &token_id)?;
+                to_binary(&token.extension.property())
+            }
+            QueryMsg::CheckAvailability {
+                token_id,
+                renting_period,
+            } => to_binary(&self.check_availability(deps, env, token_id, renting_period)?),
+            QueryMsg::QuoteReservation {
+                token_id,
+                renting_period,
+                guests,
+                add_ons,
+                voucher,
+            } => {
+                let token = self.tokens.load(deps.storage, &token_id)?;
+                let quote = parse_renting_period(&renting_period)
+                    .and_then(|(check_in, check_out)| {
+                        let mut quote = self.quote_reservation(
+                            deps.storage,
+                            &token,
+                            guests,
+                            &add_ons,
+                            check_in,
+                            check_out,
+                        )?;
+                        if let Some(code) = voucher {
+                            self.apply_voucher(
+                                deps.storage,
+                                &env,
+                                &token_id,
+                                &token,
+                                &code,
+                                &mut quote,
+                            )?;
+                        }
+                        Ok(quote)
+                    })
+                    .map_err(|err| StdError::generic_err(err.to_string()))?;
+                to_binary(&quote)
+            }
+            QueryMsg::Voucher { issuer, code } => {
+                let issuer = deps.api.addr_validate(&issuer)?;
+                to_binary(&self.vouchers.may_load(deps.storage, (&issuer, &code))?)
+            }
+            QueryMsg::TokenRating { token_id } => to_binary(&rating_response(
+                self.token_ratings.may_load(deps.storage, &token_id)?,
+            )),
+            QueryMsg::AddressRating { address } => {
+                let address = deps.api.addr_validate(&address)?;
+                to_binary(&rating_response(
+                    self.address_ratings.may_load(deps.storage, &address)?,
+                ))
+            }
+            QueryMsg::ListingTemplates { host } => {
+                to_binary(&self.query_listing_templates(deps, host)?)
+            }
+            QueryMsg::TokenHistory {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&self.query_token_history(deps, token_id, start_after, limit)?),
+            QueryMsg::MetadataHistory {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&self.query_metadata_history(deps, token_id, start_after, limit)?),
+            QueryMsg::SearchProperties {
+                city,
+                property_type,
+                min_guests,
+                min_bedrooms,
+                start_after,
+                limit,
+            } => to_binary(&self.search_properties(
+                deps,
+                city,
+                property_type,
+                min_guests,
+                min_bedrooms,
+                start_after,
+                limit,
+            )?),
+            QueryMsg::Auction { token_id } => to_binary(&self.query_auction(deps, env, token_id)?),
+            QueryMsg::SaleListing { token_id } => {
+                to_binary(&self.sales.may_load(deps.storage, &token_id)?)
+            }
+            QueryMsg::Offers {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&self.query_offers(deps, token_id, start_after, limit)?),
+            QueryMsg::RoyaltyInfo {
+                token_id,
+                sale_price,
+            } => to_binary(&self.royalty_info(deps, token_id, sale_price)?),
+            QueryMsg::CheckRoyalties {} => to_binary(&self.check_royalties(deps)?),
+            QueryMsg::DefaultRoyalty {} => to_binary(&self.default_royalty.may_load(deps.storage)?),
+            QueryMsg::Verification { token_id } => {
+                to_binary(&self.verifications.may_load(deps.storage, &token_id)?)
+            }
+            QueryMsg::Verifiers {} => to_binary(&self.query_verifiers(deps)?),
+            QueryMsg::Attestation { address } => {
+                let address = deps.api.addr_validate(&address)?;
+                to_binary(&self.attestations.may_load(deps.storage, &address)?)
+            }
+            QueryMsg::KycProviders {} => to_binary(&self.query_kyc_providers(deps)?),
+            QueryMsg::Blocklist {
+                host,
+                start_after,
+                limit,
+            } => to_binary(&self.query_blocklist(deps, host, start_after, limit)?),
+            QueryMsg::TokenRoles {
+                token_id,
+                include_expired,
+            } => to_binary(&self.query_token_roles(
+                deps,
+                env,
+                token_id,
+                include_expired.unwrap_or(false),
+            )?),
             QueryMsg::Tokens {
                 owner,
                 start_after,
@@ -396,8 +577,321 @@ where
         let minter = cw_ownable::get_ownership(deps.storage)?
             .owner
             .map(|a| a.into_string());
+        let allowed_minters = self
+            .allowed_minters
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(MinterResponse {
+            minter,
+            policy: self.get_mint_policy(deps.storage)?,
+            allowed_minters,
+        })
+    }
+
+    pub fn query_treasury_totals(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TreasuryTotalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let totals = self
+            .treasury_totals
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (denom, totals) = item?;
+                Ok(DenomTotals {
+                    balance: self.get_balance(deps.storage, denom.clone())?,
+                    denom,
+                    deposited: totals.deposited,
+                    withdrawn: totals.withdrawn,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TreasuryTotalsResponse { totals })
+    }
+
+    pub fn query_withdrawals(
+        &self,
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<WithdrawalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let withdrawals = self
+            .withdrawals
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, proposal)| proposal))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(WithdrawalsResponse { withdrawals })
+    }
+
+    pub fn query_claimable(&self, deps: Deps, address: String) -> StdResult<ClaimableResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let amounts = self
+            .claimable
+            .prefix(&address)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ClaimableResponse { amounts })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_properties(
+        &self,
+        deps: Deps,
+        city: String,
+        property_type: Option<PropertyType>,
+        min_guests: Option<u32>,
+        min_bedrooms: Option<u32>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+        let tokens = self
+            .tokens
+            .idx
+            .city
+            .prefix(normalize_city(&city))
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, token)) => token.extension.property().map_or(false, |property| {
+                    property_type
+                        .as_ref()
+                        .map_or(true, |kind| &property.property_type == kind)
+                        && property.max_guests >= min_guests.unwrap_or_default()
+                        && property.bedrooms >= min_bedrooms.unwrap_or_default()
+                }),
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| item.map(|(token_id, _)| token_id))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
+    pub fn query_auction(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+    ) -> StdResult<AuctionInfoResponse> {
+        let auction = self.auctions.load(deps.storage, &token_id)?;
+        Ok(AuctionInfoResponse {
+            islisted: true,
+            price: auction.min_bid(env.block.time.seconds()),
+            bids: auction.bids,
+            kind: auction.kind,
+            denom: auction.denom,
+            seller: auction.seller.into_string(),
+            start_time: auction.start_time,
+            end_time: auction.end_time,
+        })
+    }
+
+    pub fn check_availability(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        renting_period: Vec<String>,
+    ) -> StdResult<AvailabilityResponse> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let check = parse_renting_period(&renting_period).and_then(|(check_in, check_out)| {
+            self.check_stay_rules(&env, &token.shortterm_rental, check_in, check_out)
+        });
+        Ok(AvailabilityResponse {
+            available: check.is_ok(),
+            reason: check.err().map(|err| err.to_string()),
+        })
+    }
+
+    pub fn query_listing_templates(
+        &self,
+        deps: Deps,
+        host: String,
+    ) -> StdResult<ListingTemplatesResponse> {
+        let host = deps.api.addr_validate(&host)?;
+        let templates = self
+            .listing_templates
+            .prefix(&host)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (template_id, template) = item?;
+                let token_ids = self
+                    .template_tokens
+                    .prefix((&host, &template_id))
+                    .keys(deps.storage, None, None, Order::Ascending)
+                    .collect::<StdResult<Vec<_>>>()?;
+                Ok(ListingTemplateInfo {
+                    template_id,
+                    template,
+                    token_ids,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ListingTemplatesResponse { templates })
+    }
+
+    pub fn query_token_history(
+        &self,
+        deps: Deps,
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<TokenHistoryResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let entries = self
+            .provenance
+            .prefix(&token_id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(seq, event)| ProvenanceEntry { seq, event }))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokenHistoryResponse { entries })
+    }
+
+    pub fn query_metadata_history(
+        &self,
+        deps: Deps,
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<MetadataHistoryResponse<T>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let versions = self
+            .metadata_history
+            .prefix(&token_id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, version)| version))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(MetadataHistoryResponse { versions })
+    }
+
+    pub fn query_offers(
+        &self,
+        deps: Deps,
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OffersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let offers = self
+            .offers
+            .prefix(&token_id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, offer)| offer))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(OffersResponse { offers })
+    }
+
+    pub fn query_verifiers(&self, deps: Deps) -> StdResult<VerifiersResponse> {
+        let verifiers = self
+            .verifiers
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(VerifiersResponse {
+            verifiers,
+            required: self.is_verification_required(deps.storage)?,
+        })
+    }
+
+    pub fn query_kyc_providers(&self, deps: Deps) -> StdResult<KycProvidersResponse> {
+        let providers = self
+            .kyc_providers
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(KycProvidersResponse { providers })
+    }
+
+    pub fn query_blocklist(
+        &self,
+        deps: Deps,
+        host: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BlocklistResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let addresses = match maybe_addr(deps.api, host)? {
+            Some(host) => self
+                .host_blocklists
+                .prefix(&host)
+                .keys(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|addr| addr.map(String::from))
+                .collect::<StdResult<Vec<_>>>()?,
+            None => self
+                .global_blocklist
+                .keys(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|addr| addr.map(String::from))
+                .collect::<StdResult<Vec<_>>>()?,
+        };
 
-        Ok(MinterResponse { minter })
+        Ok(BlocklistResponse { addresses })
+    }
+
+    pub fn query_token_roles(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: bool,
+    ) -> StdResult<TokenRolesResponse> {
+        let mut roles = vec![];
+        for item in self
+            .roles
+            .prefix(&token_id)
+            .range(deps.storage, None, None, Order::Ascending)
+        {
+            let (grantee, grants) = item?;
+            roles.extend(
+                grants
+                    .into_iter()
+                    .filter(|grant| include_expired || !grant.expires.is_expired(&env.block))
+                    .map(|grant| RoleAssignment {
+                        grantee: grantee.to_string(),
+                        role: grant.role,
+                        expires: grant.expires,
+                    }),
+            );
+        }
+
+        Ok(TokenRolesResponse { roles })
     }
 
     pub fn ownership(deps: Deps) -> StdResult<cw_ownable::Ownership<Addr>> {
@@ -430,3 +924,15 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
         expires: approval.expires,
     }
 }
+
+fn rating_response(summary: Option<RatingSummary>) -> RatingResponse {
+    let summary = summary.unwrap_or_default();
+    RatingResponse {
+        count: summary.count,
+        average: if summary.count == 0 {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(summary.total, summary.count)
+        },
+    }
+}
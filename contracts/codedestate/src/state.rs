@@ -3,14 +3,23 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, CustomMsg, StdResult, Storage, Uint128};
+use cosmwasm_std::{
+    Addr, BankMsg, BlockInfo, Coin, CustomMsg, Decimal, Empty, Event, Order, StdError, StdResult,
+    Storage, Uint128,
+};
 
-use cw721::{ContractInfoResponse, Cw721, Expiration, LongTermRental, ShortTermRental};
+use cw721::{
+    AuctionKind, Bid, CancellationItem, ContractInfoResponse, Cw721, Expiration, LongTermRental,
+    ShortTermRental,
+};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
+use crate::error::ContractError;
+use crate::property::{normalize_city, PropertyExtension};
+
 pub struct Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     Q: CustomMsg,
     E: CustomMsg,
 {
@@ -18,9 +27,71 @@ where
     pub token_count: Item<'a, u64>,
     pub fee: Item<'a, u64>,
     pub balances: Map<'a, &'a str, Uint128>,
+    /// Cumulative amounts that ever entered and left `balances`, per denom
+    pub treasury_totals: Map<'a, &'a str, TreasuryTotals>,
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Controls over large treasury withdrawals, unset until the owner configures it
+    pub treasury_config: Item<'a, TreasuryConfig>,
+    pub withdrawal_count: Item<'a, u64>,
+    pub withdrawals: Map<'a, u64, WithdrawalProposal>,
+    /// Payout preferences set for a single token, cleared when the token changes hands
+    pub token_payouts: Map<'a, &'a str, PayoutConfig>,
+    /// Default payout preferences of a host, used for tokens without their own
+    pub host_payouts: Map<'a, &'a Addr, PayoutConfig>,
+    /// Held payouts waiting to be claimed, keyed by (recipient, denom)
+    pub claimable: Map<'a, (&'a Addr, &'a str), Uint128>,
+    /// Who may mint, unset means only the contract owner
+    pub mint_policy: Item<'a, MintPolicy>,
+    /// Property issuers allowed to mint under `MintPolicy::Allowlist`
+    pub allowed_minters: Map<'a, &'a Addr, Empty>,
+    /// Fixed-price sale listings, cleared when the token changes hands
+    pub sales: Map<'a, &'a str, SaleListing>,
+    /// Escrowed purchase offers keyed by (token, buyer)
+    pub offers: Map<'a, (&'a str, &'a Addr), Offer>,
+    /// Running auctions, a token cannot be transferred while it is auctioned
+    pub auctions: Map<'a, &'a str, Auction>,
+    /// Tokens locked into shares, see [`Fraction`]
+    pub fractions: Map<'a, &'a str, Fraction>,
+    pub shareholders: Map<'a, (&'a str, &'a Addr), Shareholder>,
+    /// Every metadata version of a token keyed by (token, version), starting at 1 on mint
+    pub metadata_history: Map<'a, (&'a str, u64), MetadataVersion<T>>,
+    /// Append-only log of what happened to a token keyed by (token, sequence), kept after burning
+    pub provenance: Map<'a, (&'a str, u64), Event>,
+    /// Named short term listing settings keyed by (host, template)
+    pub listing_templates: Map<'a, (&'a Addr, &'a str), ListingTemplate>,
+    /// Template a token was listed with, as (host, template)
+    pub template_links: Map<'a, &'a str, (Addr, String)>,
+    /// Reverse of `template_links` keyed by (host, template, token)
+    pub template_tokens: Map<'a, (&'a Addr, &'a str, &'a str), Empty>,
+    /// Promo codes for short term bookings keyed by (issuer, code)
+    pub vouchers: Map<'a, (&'a Addr, &'a str), Voucher>,
+    /// Finalized stays keyed by (token, traveler, check-in), kept for reviews
+    pub completed_stays: Map<'a, (&'a str, &'a Addr, u64), CompletedStay>,
+    /// Reviews keyed by (token, check-in, author), stays of a token never share a check-in
+    pub reviews: Map<'a, (&'a str, u64, &'a Addr), Review>,
+    /// Ratings travelers gave each token
+    pub token_ratings: Map<'a, &'a str, RatingSummary>,
+    /// Ratings each host or traveler received
+    pub address_ratings: Map<'a, &'a Addr, RatingSummary>,
+    /// Royalty for tokens without an entry in `royalties`
+    pub default_royalty: Item<'a, Royalty>,
+    pub royalties: Map<'a, &'a str, Royalty>,
+    /// Accounts appointed by the contract owner to attest properties
+    pub verifiers: Map<'a, &'a Addr, Empty>,
+    pub verifications: Map<'a, &'a str, Verification>,
+    /// When set, tokens must be verified before they can be listed
+    pub require_verification: Item<'a, bool>,
+    /// Accounts appointed by the contract owner to attest guest identities
+    pub kyc_providers: Map<'a, &'a Addr, Empty>,
+    pub attestations: Map<'a, &'a Addr, GuestAttestation>,
+    /// Addresses the contract owner barred from booking anywhere
+    pub global_blocklist: Map<'a, &'a Addr, Empty>,
+    /// Guests a host refuses, keyed by (host, guest)
+    pub host_blocklists: Map<'a, (&'a Addr, &'a Addr), Empty>,
+    /// Scoped permissions per (token, grantee), cleared when the token changes hands
+    pub roles: Map<'a, (&'a str, &'a Addr), Vec<RoleGrant>>,
 
     pub(crate) _custom_response: PhantomData<C>,
     pub(crate) _custom_query: PhantomData<Q>,
@@ -30,7 +101,7 @@ where
 // This is a signal, the implementations are in other files
 impl<'a, T, C, E, Q> Cw721<T, C> for Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
@@ -39,7 +110,7 @@ where
 
 impl<T, C, E, Q> Default for Cw721Contract<'static, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     E: CustomMsg,
     Q: CustomMsg,
 {
@@ -58,7 +129,7 @@ where
 
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
     E: CustomMsg,
     Q: CustomMsg,
 {
@@ -73,6 +144,7 @@ where
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
+            city: MultiIndex::new(token_city_idx, tokens_key, "tokens__city"),
         };
         Self {
             contract_info: Item::new(contract_key),
@@ -80,7 +152,41 @@ where
             fee: Item::new(fee_key),
             operators: Map::new(operator_key),
             balances: Map::new(balance_key),
+            treasury_totals: Map::new("treasury_totals"),
             tokens: IndexedMap::new(tokens_key, indexes),
+            treasury_config: Item::new("treasury_config"),
+            withdrawal_count: Item::new("withdrawal_count"),
+            withdrawals: Map::new("withdrawals"),
+            token_payouts: Map::new("token_payouts"),
+            host_payouts: Map::new("host_payouts"),
+            claimable: Map::new("claimable"),
+            mint_policy: Item::new("mint_policy"),
+            allowed_minters: Map::new("allowed_minters"),
+            sales: Map::new("sales"),
+            offers: Map::new("offers"),
+            auctions: Map::new("auctions"),
+            fractions: Map::new("fractions"),
+            shareholders: Map::new("shareholders"),
+            metadata_history: Map::new("metadata_history"),
+            provenance: Map::new("provenance"),
+            listing_templates: Map::new("listing_templates"),
+            template_links: Map::new("template_links"),
+            template_tokens: Map::new("template_tokens"),
+            vouchers: Map::new("vouchers"),
+            completed_stays: Map::new("completed_stays"),
+            reviews: Map::new("reviews"),
+            token_ratings: Map::new("token_ratings"),
+            address_ratings: Map::new("address_ratings"),
+            default_royalty: Item::new("default_royalty"),
+            royalties: Map::new("royalties"),
+            verifiers: Map::new("verifiers"),
+            verifications: Map::new("verifications"),
+            require_verification: Item::new("require_verification"),
+            kyc_providers: Map::new("kyc_providers"),
+            attestations: Map::new("attestations"),
+            global_blocklist: Map::new("global_blocklist"),
+            host_blocklists: Map::new("host_blocklists"),
+            roles: Map::new("roles"),
             _custom_response: PhantomData,
             _custom_execute: PhantomData,
             _custom_query: PhantomData,
@@ -96,6 +202,57 @@ where
         Ok(fee)
     }
 
+    pub fn get_mint_policy(&self, storage: &dyn Storage) -> StdResult<MintPolicy> {
+        Ok(self.mint_policy.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn token_offers(&self, storage: &dyn Storage, token_id: &str) -> StdResult<Vec<Offer>> {
+        self.offers
+            .prefix(token_id)
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, offer)| offer))
+            .collect()
+    }
+
+    /// Royalty that applies to `token_id`, the token's own or the contract default
+    pub fn royalty(&self, storage: &dyn Storage, token_id: &str) -> StdResult<Option<Royalty>> {
+        match self.royalties.may_load(storage, token_id)? {
+            Some(royalty) => Ok(Some(royalty)),
+            None => self.default_royalty.may_load(storage),
+        }
+    }
+
+    /// Whether `token_id` is locked into shares that were not bought out yet
+    pub fn is_fractionalized(&self, storage: &dyn Storage, token_id: &str) -> StdResult<bool> {
+        Ok(self
+            .fractions
+            .may_load(storage, token_id)?
+            .map_or(false, |fraction| fraction.bought_out_by.is_none()))
+    }
+
+    /// Shareholder of `token_id` with the dividends accrued up to the current index
+    pub fn shareholder(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        holder: &Addr,
+    ) -> StdResult<Shareholder> {
+        let fraction = self.fractions.load(storage, token_id)?;
+        let mut shareholder = self
+            .shareholders
+            .may_load(storage, (token_id, holder))?
+            .unwrap_or_default();
+        shareholder.accrue(fraction.dividend_index);
+        Ok(shareholder)
+    }
+
+    pub fn is_verification_required(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self
+            .require_verification
+            .may_load(storage)?
+            .unwrap_or_default())
+    }
+
     pub fn token_count(&self, storage: &dyn Storage) -> StdResult<u64> {
         Ok(self.token_count.may_load(storage)?.unwrap_or_default())
     }
@@ -104,28 +261,95 @@ where
         Ok(self.balances.may_load(storage, &denom)?.unwrap_or_default())
     }
 
-    pub fn increase_balance(
+    /// Credits `amount` to the fee treasury. All increases of `balances` go through here,
+    /// the returned event should be added to the response as the audit trail.
+    pub fn treasury_deposit(&self, storage: &mut dyn Storage, amount: &Coin) -> StdResult<Event> {
+        let balance = self
+            .get_balance(storage, amount.denom.clone())?
+            .checked_add(amount.amount)?;
+        self.balances.save(storage, &amount.denom, &balance)?;
+
+        let mut totals = self.get_treasury_totals(storage, &amount.denom)?;
+        totals.deposited = totals.deposited.checked_add(amount.amount)?;
+        self.treasury_totals.save(storage, &amount.denom, &totals)?;
+
+        Ok(treasury_event("deposit", amount, balance))
+    }
+
+    /// Debits `amount` from the fee treasury, failing if the balance does not cover it.
+    /// All decreases of `balances` go through here.
+    pub fn treasury_withdraw(
         &self,
         storage: &mut dyn Storage,
-        denom: String,
-        amount: Uint128,
-    ) -> StdResult<Uint128> {
-        let mut balance = self.balances.may_load(storage, &denom)?.unwrap_or_default();
-        balance += amount;
-        self.balances.save(storage, &denom, &balance)?;
-        Ok(balance)
+        amount: &Coin,
+    ) -> Result<Event, ContractError> {
+        let balance = self
+            .get_balance(storage, amount.denom.clone())?
+            .checked_sub(amount.amount)
+            .map_err(|_| ContractError::UnavailableAmount {})?;
+        self.balances.save(storage, &amount.denom, &balance)?;
+
+        let mut totals = self.get_treasury_totals(storage, &amount.denom)?;
+        totals.withdrawn = totals
+            .withdrawn
+            .checked_add(amount.amount)
+            .map_err(StdError::from)?;
+        self.treasury_totals.save(storage, &amount.denom, &totals)?;
+
+        Ok(treasury_event("withdraw", amount, balance))
+    }
+
+    pub fn get_treasury_totals(
+        &self,
+        storage: &dyn Storage,
+        denom: &str,
+    ) -> StdResult<TreasuryTotals> {
+        Ok(self
+            .treasury_totals
+            .may_load(storage, denom)?
+            .unwrap_or_default())
+    }
+
+    pub fn next_withdrawal_id(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let id = self.withdrawal_count.may_load(storage)?.unwrap_or_default() + 1;
+        self.withdrawal_count.save(storage, &id)?;
+        Ok(id)
+    }
+
+    /// Resolves where the proceeds of `token_id` go: the token's own config first,
+    /// then the host default, and otherwise everything to `owner`.
+    pub fn payout_config(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        owner: &Addr,
+    ) -> StdResult<PayoutConfig> {
+        if let Some(config) = self.token_payouts.may_load(storage, token_id)? {
+            return Ok(config);
+        }
+        if let Some(config) = self.host_payouts.may_load(storage, owner)? {
+            return Ok(config);
+        }
+        Ok(PayoutConfig {
+            shares: vec![PayoutShare {
+                address: owner.clone(),
+                percentage: 100,
+            }],
+            hold: false,
+        })
     }
 
-    pub fn decrease_balance(
+    pub fn add_claimable(
         &self,
         storage: &mut dyn Storage,
-        denom: String,
-        amount: Uint128,
+        recipient: &Addr,
+        amount: &Coin,
     ) -> StdResult<Uint128> {
-        let mut balance = self.balances.may_load(storage, &denom)?.unwrap_or_default();
-        balance -= amount;
-        self.balances.save(storage, &denom, &balance)?;
-        Ok(balance)
+        self.claimable.update(
+            storage,
+            (recipient, &amount.denom),
+            |held| -> StdResult<_> { Ok(held.unwrap_or_default().checked_add(amount.amount)?) },
+        )
     }
 
     pub fn increment_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
@@ -172,6 +396,49 @@ pub struct TokenInfo<T> {
     pub extension: T,
 }
 
+impl<T> TokenInfo<T> {
+    /// A freshly minted token, not listed for any kind of rental
+    pub fn new(owner: Addr, token_uri: Option<String>, extension: T) -> Self {
+        TokenInfo {
+            owner,
+            approvals: vec![],
+            longterm_rental: LongTermRental {
+                islisted: None,
+                isreserved: None,
+                landlord: None,
+                tenant: None,
+                tenant_address: None,
+                deposit_amount: Uint128::zero(),
+                withdrawn_amount: Uint128::zero(),
+                renting_flag: None,
+                ejari_flag: None,
+            },
+            shortterm_rental: ShortTermRental {
+                islisted: None,
+                travelers: vec![],
+                price_per_day: 0,
+                available_period: vec![],
+                deposit_amount: Uint128::zero(),
+                withdrawn_amount: Uint128::zero(),
+                denom: "unibi".to_string(),
+                auto_approve: false,
+                cancellation: vec![],
+                minimum_stay: 0,
+                maximum_stay: 0,
+                booking_horizon: 0,
+                advance_notice: 0,
+                buffer_days: 0,
+                base_guests: 0,
+                extra_guest_fee: 0,
+                add_ons: vec![],
+                min_kyc_level: 0,
+            },
+            token_uri,
+            extension,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Approval {
     /// Account that can transfer/send the token
@@ -186,6 +453,364 @@ impl Approval {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TreasuryTotals {
+    pub deposited: Uint128,
+    pub withdrawn: Uint128,
+}
+
+fn treasury_event(movement: &str, amount: &Coin, balance: Uint128) -> Event {
+    Event::new("treasury")
+        .add_attribute("movement", movement)
+        .add_attribute("denom", amount.denom.clone())
+        .add_attribute("amount", amount.amount)
+        .add_attribute("balance", balance)
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListingTemplate {
+    pub denom: String,
+    pub price_per_day: u64,
+    pub auto_approve: bool,
+    pub minimum_stay: u64,
+    pub cancellation: Vec<CancellationItem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum VoucherDiscount {
+    /// Percent off the rent, from 1 to 100
+    Percentage { percent: u64 },
+    /// Amount off the rent, only for listings in `denom`
+    Fixed { amount: Uint128, denom: String },
+}
+
+/// A promo code, issued by the contract owner or by a host for their own tokens.
+/// Discounts apply to the rent, add-ons are charged in full.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Voucher {
+    pub issuer: Addr,
+    pub discount: VoucherDiscount,
+    /// Bookings the code can be used for, `None` for no cap
+    pub max_uses: Option<u32>,
+    pub uses: u32,
+    pub expires: Expiration,
+    /// Tokens the code is limited to, empty for any
+    pub token_ids: Vec<String>,
+    /// Hosts whose tokens the code is limited to, empty for any
+    pub hosts: Vec<Addr>,
+}
+
+impl Voucher {
+    /// Discount on `rent` for a booking of `token_id`, owned by `host`
+    pub fn discount(
+        &self,
+        block: &BlockInfo,
+        token_id: &str,
+        host: &Addr,
+        denom: &str,
+        rent: Uint128,
+    ) -> Result<Uint128, ContractError> {
+        if self.expires.is_expired(block) {
+            return Err(ContractError::VoucherExpired {});
+        }
+        if self
+            .max_uses
+            .map_or(false, |max_uses| self.uses >= max_uses)
+        {
+            return Err(ContractError::VoucherExhausted {});
+        }
+        if (!self.token_ids.is_empty() && !self.token_ids.iter().any(|id| id == token_id))
+            || (!self.hosts.is_empty() && !self.hosts.contains(host))
+        {
+            return Err(ContractError::VoucherNotApplicable {});
+        }
+        let discount = match &self.discount {
+            VoucherDiscount::Percentage { percent } => rent.multiply_ratio(*percent, 100u64),
+            VoucherDiscount::Fixed {
+                amount,
+                denom: voucher_denom,
+            } => {
+                if voucher_denom != denom {
+                    return Err(ContractError::VoucherNotApplicable {});
+                }
+                *amount
+            }
+        };
+        Ok(discount.min(rent))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CompletedStay {
+    /// Owner of the token while the stay took place
+    pub host: Addr,
+    pub check_out: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Review {
+    pub reviewee: Addr,
+    /// From 1 to 5
+    pub rating: u8,
+    pub comment_uri: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct RatingSummary {
+    pub count: u64,
+    pub total: u64,
+}
+
+impl RatingSummary {
+    pub fn with_rating(mut self, rating: u8) -> Self {
+        self.count += 1;
+        self.total += u64::from(rating);
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataVersion<T> {
+    pub version: u64,
+    pub editor: Addr,
+    pub updated_at: u64,
+    pub token_uri: Option<String>,
+    pub extension: T,
+}
+
+/// Payouts and treasury events produced while settling escrowed rent,
+/// to be added to the response of the calling handler
+#[derive(Default)]
+pub struct Settlement {
+    pub messages: Vec<BankMsg>,
+    pub events: Vec<Event>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TreasuryConfig {
+    /// Seconds a proposed withdrawal must wait before it can be executed
+    pub timelock: u64,
+    /// Largest amount per denom that `Withdraw` may move without a proposal.
+    /// Denoms not listed here always require a proposal.
+    pub instant_limits: Vec<Coin>,
+    /// Accounts allowed to approve proposed withdrawals
+    pub signers: Vec<Addr>,
+    /// Number of signer approvals needed before a proposal can be executed
+    pub threshold: u32,
+}
+
+impl TreasuryConfig {
+    pub fn instant_limit(&self, denom: &str) -> Uint128 {
+        self.instant_limits
+            .iter()
+            .find(|c| c.denom == denom)
+            .map(|c| c.amount)
+            .unwrap_or_default()
+    }
+
+    pub fn is_signer(&self, addr: &Addr) -> bool {
+        self.signers.contains(addr)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct WithdrawalProposal {
+    pub id: u64,
+    pub proposer: Addr,
+    pub target: Addr,
+    pub amount: Coin,
+    /// Signers that approved this withdrawal so far
+    pub approvals: Vec<Addr>,
+    /// Block time (in seconds) from which the withdrawal can be executed
+    pub executable_at: u64,
+    pub status: WithdrawalStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MintPolicy {
+    /// Only the contract owner can mint
+    #[default]
+    OwnerOnly,
+    /// The contract owner and the allowed minters can mint
+    Allowlist,
+    /// Anyone can mint, paying `fee` to the treasury if set.
+    /// The contract owner never pays the fee
+    Open { fee: Option<Coin> },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SaleListing {
+    pub seller: Addr,
+    pub price: Coin,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Offer {
+    pub buyer: Addr,
+    /// Funds held by the contract until the offer is accepted or cancelled
+    pub amount: Coin,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Auction {
+    pub seller: Addr,
+    pub kind: AuctionKind,
+    pub denom: String,
+    /// Opening bid of an English auction, starting price of a Dutch one
+    pub start_price: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Accepted bids in order. Only the last, highest one is held in escrow
+    pub bids: Vec<Bid>,
+}
+
+/// A token locked into shares. The curator keeps managing its rentals while rent
+/// paid in the buyout denom is shared out to the shareholders
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Fraction {
+    pub curator: Addr,
+    pub total_shares: Uint128,
+    /// Shares not redeemed yet, the fraction is removed once this reaches zero
+    pub outstanding_shares: Uint128,
+    pub buyout_price: Coin,
+    /// Dividends earned per share since the token was fractionalized
+    pub dividend_index: Decimal,
+    /// Rent that didn't divide evenly across the shares, added to the next distribution
+    #[serde(default)]
+    pub undistributed: Uint128,
+    pub bought_out_by: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Shareholder {
+    pub shares: Uint128,
+    /// Dividend index up to which `pending` was credited
+    pub index: Decimal,
+    /// Dividends credited but not claimed yet
+    pub pending: Uint128,
+}
+
+impl Shareholder {
+    pub fn accrue(&mut self, index: Decimal) {
+        self.pending += self.shares * (index - self.index);
+        self.index = index;
+    }
+}
+
+impl Auction {
+    /// Lowest bid accepted at block time `time`
+    pub fn min_bid(&self, time: u64) -> u64 {
+        match &self.kind {
+            AuctionKind::English { min_increment, .. } => match self.bids.last() {
+                Some(bid) => bid.offer.saturating_add(*min_increment),
+                None => self.start_price,
+            },
+            AuctionKind::Dutch { floor_price } => {
+                let duration = self.end_time - self.start_time;
+                let elapsed = time.saturating_sub(self.start_time).min(duration);
+                let decay = u128::from(self.start_price - floor_price) * u128::from(elapsed)
+                    / u128::from(duration);
+                self.start_price - decay as u64
+            }
+        }
+    }
+
+    /// Returns the escrowed highest bid to its bidder
+    pub fn refund_highest_bid(&self) -> Option<BankMsg> {
+        self.bids.last().map(|bid| BankMsg::Send {
+            to_address: bid.buyer.clone(),
+            amount: vec![Coin {
+                denom: self.denom.clone(),
+                amount: Uint128::from(bid.offer),
+            }],
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Royalty {
+    pub payment_address: Addr,
+    /// Share of the sale price in basis points (/10000)
+    pub share: u64,
+}
+
+impl Royalty {
+    pub fn amount(&self, sale_price: Uint128) -> Uint128 {
+        sale_price.multiply_ratio(self.share, 10000u64)
+    }
+}
+
+/// Attestation that a token corresponds to a real property
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Verification {
+    pub verifier: Addr,
+    /// Where the evidence lives, e.g. a title deed, Ejari or permit number
+    pub attestation_uri: String,
+    /// Block time (in seconds) of the verification
+    pub verified_at: u64,
+}
+
+/// Attestation that an address passed identity checks
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct GuestAttestation {
+    pub provider: Addr,
+    /// Depth of the checks, higher is stricter
+    pub level: u8,
+    pub expires: Expiration,
+    /// Block time (in seconds) of the attestation
+    pub attested_at: u64,
+}
+
+impl GuestAttestation {
+    pub fn satisfies(&self, block: &BlockInfo, min_level: u8) -> bool {
+        self.level >= min_level && !self.expires.is_expired(block)
+    }
+}
+
+/// Limited delegations a token owner can hand out instead of full cw721 approvals
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenRole {
+    /// Approve, reject and finalize short-term reservations
+    ReservationManager,
+    /// List and unlist the token for short-term rental
+    PricingManager,
+    /// Update the token metadata
+    MetadataEditor,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoleGrant {
+    pub role: TokenRole,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PayoutShare {
+    pub address: Addr,
+    /// Share of every payout, all shares of a config add up to 100
+    pub percentage: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PayoutConfig {
+    pub shares: Vec<PayoutShare>,
+    /// Keep the shares in the contract until each recipient claims them
+    pub hold: bool,
+}
+
 // #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 // pub struct Bid {
 //     /// Account that can transfer/send the token
@@ -196,17 +821,19 @@ impl Approval {
 
 pub struct TokenIndexes<'a, T>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
 {
     pub owner: MultiIndex<'a, Addr, TokenInfo<T>, String>,
+    /// Tokens by normalized city, tokens without property metadata are under an empty key
+    pub city: MultiIndex<'a, String, TokenInfo<T>, String>,
 }
 
 impl<'a, T> IndexList<TokenInfo<T>> for TokenIndexes<'a, T>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
 {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenInfo<T>>> + '_> {
-        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner];
+        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner, &self.city];
         Box::new(v.into_iter())
     }
 }
@@ -214,3 +841,10 @@ where
 pub fn token_owner_idx<T>(_pk: &[u8], d: &TokenInfo<T>) -> Addr {
     d.owner.clone()
 }
+
+pub fn token_city_idx<T: PropertyExtension>(_pk: &[u8], d: &TokenInfo<T>) -> String {
+    d.extension
+        .property()
+        .map(|property| normalize_city(&property.address.city))
+        .unwrap_or_default()
+}
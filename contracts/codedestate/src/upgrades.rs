@@ -0,0 +1,130 @@
+use cosmwasm_std::{attr, Addr, Api, Attribute, CustomMsg, DepsMut, Order, StdResult, Storage};
+use cw721::{LongTermRental, ShortTermRental};
+use cw_storage_plus::{Item, Map};
+use semver::Version;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+use crate::property::PropertyExtension;
+use crate::state::{Approval, Cw721Contract, TokenInfo};
+
+/// Where cw721-base up to 0.16 kept the minter, replaced by cw_ownable
+const LEGACY_MINTER: Item<Addr> = Item::new("minter");
+
+/// A token in any of the layouts it was stored in. The rental fields were added after
+/// cw721-base, later `ShortTermRental` and `Traveler` fields have serde defaults.
+#[derive(Serialize, Deserialize)]
+struct StoredToken<T> {
+    owner: Addr,
+    #[serde(default)]
+    approvals: Vec<Approval>,
+    longterm_rental: Option<LongTermRental>,
+    shortterm_rental: Option<ShortTermRental>,
+    token_uri: Option<String>,
+    extension: T,
+}
+
+/// Runs the data migrations introduced after `from`, oldest first. Every step can be
+/// run again on data it already migrated.
+pub fn migrate<T, C, E, Q>(deps: DepsMut, from: &Version) -> Result<Vec<Attribute>, ContractError>
+where
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    let mut applied = vec![];
+    if *from < Version::new(0, 17, 0) {
+        move_minter_to_ownable(deps.storage, deps.api)?;
+        applied.push(attr("migration", "0.17.0 minter to ownership"));
+    }
+    if *from < Version::new(0, 18, 0) {
+        let count = rewrite_tokens::<T, C, E, Q>(deps.storage)?;
+        applied.push(attr("migration", "0.18.0 token layout"));
+        applied.push(attr("tokens_rewritten", count.to_string()));
+    }
+    if *from < Version::new(0, 19, 0) {
+        let count = record_stay_hosts::<T, C, E, Q>(deps.storage)?;
+        applied.push(attr("migration", "0.19.0 stay hosts"));
+        applied.push(attr("tokens_updated", count.to_string()));
+    }
+    Ok(applied)
+}
+
+/// Hands the legacy minter's rights to the cw_ownable owner, unless one was already set
+fn move_minter_to_ownable(storage: &mut dyn Storage, api: &dyn Api) -> StdResult<()> {
+    if let Some(minter) = LEGACY_MINTER.may_load(storage)? {
+        LEGACY_MINTER.remove(storage);
+        let owned = cw_ownable::get_ownership(storage)
+            .map(|ownership| ownership.owner.is_some())
+            .unwrap_or(false);
+        if !owned {
+            cw_ownable::initialize_owner(storage, api, Some(minter.as_str()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves every token in the current layout, filling in rentals for tokens minted before
+/// they existed and indexing tokens minted before the city index
+fn rewrite_tokens<T, C, E, Q>(storage: &mut dyn Storage) -> StdResult<usize>
+where
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    let contract = Cw721Contract::<T, C, E, Q>::default();
+    let stored: Map<&str, StoredToken<T>> = Map::new("tokens");
+    let tokens = stored
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let count = tokens.len();
+    for (token_id, stored) in tokens {
+        let mut token = TokenInfo::new(stored.owner, stored.token_uri, stored.extension);
+        token.approvals = stored.approvals;
+        if let Some(longterm_rental) = stored.longterm_rental {
+            token.longterm_rental = longterm_rental;
+        }
+        if let Some(shortterm_rental) = stored.shortterm_rental {
+            token.shortterm_rental = shortterm_rental;
+        }
+        // the old value may not parse as a `TokenInfo`, so only add index entries
+        contract
+            .tokens
+            .replace(storage, &token_id, Some(&token), None)?;
+    }
+    Ok(count)
+}
+
+/// Records the owner as host of the stays booked before hosts were kept with the
+/// booking, so a later transfer doesn't hand their reviews to the new owner
+fn record_stay_hosts<T, C, E, Q>(storage: &mut dyn Storage) -> StdResult<usize>
+where
+    T: Serialize + DeserializeOwned + Clone + PropertyExtension,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    let contract = Cw721Contract::<T, C, E, Q>::default();
+    let tokens = contract
+        .tokens
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut count = 0;
+    for (token_id, mut token) in tokens {
+        let owner = token.owner.clone();
+        let mut updated = false;
+        for traveler in token.shortterm_rental.travelers.iter_mut() {
+            if traveler.host.is_none() {
+                traveler.host = Some(owner.clone());
+                updated = true;
+            }
+        }
+        if updated {
+            contract.tokens.save(storage, &token_id, &token)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -7,10 +7,11 @@ pub use cw_utils::Expiration;
 
 pub use crate::msg::Cw721ExecuteMsg;
 pub use crate::query::{
-    AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, AuctionInfoResponse, Bid,
-    CancellationItem, ContractInfoResponse, Cw721QueryMsg, FeeValueResponse, Host, Landlord,
+    AddOn, AddOnPricing, AddOnRefund, AllNftInfoResponse, Approval, ApprovalResponse,
+    ApprovalsResponse, AuctionInfoResponse, AuctionKind, Bid, BookedAddOn, CancellationItem,
+    CheckRoyaltiesResponse, ContractInfoResponse, Cw721QueryMsg, FeeValueResponse, Host, Landlord,
     LongTermRental, NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse,
-    OwnerOfResponse, ShortTermRental, Tenant, TokensResponse, Traveler,
+    OwnerOfResponse, RoyaltiesInfoResponse, ShortTermRental, Tenant, TokensResponse, Traveler,
 };
 pub use crate::receiver::Cw721ReceiveMsg;
 pub use crate::traits::{Cw721, Cw721Execute, Cw721Query};
@@ -73,6 +73,17 @@ pub enum Cw721QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+
+    /// CW2981 royalties.
+    /// Returns who should receive a royalty on a sale of the token at `sale_price`
+    /// and how much: `RoyaltiesInfoResponse`
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    /// CW2981 royalties.
+    /// Whether the contract implements royalties: `CheckRoyaltiesResponse`
+    CheckRoyalties {},
 }
 
 #[cw_serde]
@@ -128,6 +139,21 @@ pub struct Traveler {
     pub cancelled: bool,
     pub renting_period: Vec<u64>,
     pub address: Option<Addr>,
+    /// Block time (in seconds) up to which the rent was already paid out to a previous owner
+    #[serde(default)]
+    pub settled_until: u64,
+    #[serde(default = "default_guests")]
+    pub guests: u32,
+    /// Add-ons paid with the booking, included in `deposit_amount`
+    #[serde(default)]
+    pub add_ons: Vec<BookedAddOn>,
+    /// Owner hosting the stay, handed over when the token changes hands before check-out
+    #[serde(default)]
+    pub host: Option<Addr>,
+}
+
+fn default_guests() -> u32 {
+    1
 }
 
 #[cw_serde]
@@ -149,6 +175,41 @@ pub struct CancellationItem {
     pub percentage: u64,
 }
 
+#[cw_serde]
+pub enum AddOnPricing {
+    Flat,
+    PerNight,
+}
+
+/// What happens to an add-on's price when an approved booking is cancelled
+#[cw_serde]
+pub enum AddOnRefund {
+    /// Always refunded in full
+    Full,
+    /// Refunded like the rent, following the listing's cancellation policy
+    Policy,
+    /// Never refunded
+    None,
+}
+
+/// An extra service or fee of a short term listing
+#[cw_serde]
+pub struct AddOn {
+    pub id: String,
+    pub price: u64,
+    pub pricing: AddOnPricing,
+    /// Charged on every booking instead of being picked by the guest
+    pub mandatory: bool,
+    pub refund: AddOnRefund,
+}
+
+#[cw_serde]
+pub struct BookedAddOn {
+    pub id: String,
+    pub amount: Uint128,
+    pub refund: AddOnRefund,
+}
+
 #[cw_serde]
 pub struct ShortTermRental {
     pub islisted: Option<bool>,
@@ -161,6 +222,29 @@ pub struct ShortTermRental {
     pub withdrawn_amount: Uint128,
     pub cancellation: Vec<CancellationItem>,
     pub minimum_stay: u64,
+    /// Longest stay in days, 0 for no limit
+    #[serde(default)]
+    pub maximum_stay: u64,
+    /// How many days ahead a stay may start, 0 for no limit
+    #[serde(default)]
+    pub booking_horizon: u64,
+    /// Seconds that have to pass between booking and check-in
+    #[serde(default)]
+    pub advance_notice: u64,
+    /// Days kept free between two stays for cleaning
+    #[serde(default)]
+    pub buffer_days: u64,
+    /// Guests included in `price_per_day`, with 0 every guest pays `extra_guest_fee`
+    #[serde(default)]
+    pub base_guests: u32,
+    /// Charged per night for every guest above `base_guests`
+    #[serde(default)]
+    pub extra_guest_fee: u64,
+    #[serde(default)]
+    pub add_ons: Vec<AddOn>,
+    /// Identity attestation level guests need to book, 0 for none
+    #[serde(default)]
+    pub min_kyc_level: u8,
 }
 
 #[cw_serde]
@@ -210,13 +294,33 @@ pub struct NftInfoResponse<T> {
     pub extension: T,
 }
 
+#[cw_serde]
+pub enum AuctionKind {
+    /// Ascending bids, the highest bid wins at the end if it reaches `reserve_price`.
+    /// Each bid must beat the previous one by `min_increment`, and a bid within
+    /// `extension` seconds of the end pushes the end back to `extension` seconds from then
+    English {
+        reserve_price: u64,
+        min_increment: u64,
+        extension: u64,
+    },
+    /// The price falls linearly from the start price to `floor_price` over the
+    /// auction, the first bid reaching the current price wins
+    Dutch { floor_price: u64 },
+}
+
 #[cw_serde]
 pub struct AuctionInfoResponse {
     pub islisted: bool,
-    /// Token price for auction
+    /// Token price for auction: the lowest acceptable bid right now
     pub price: u64,
     /// bids of buyers for this token
     pub bids: Vec<Bid>,
+    pub kind: AuctionKind,
+    pub denom: String,
+    pub seller: String,
+    pub start_time: u64,
+    pub end_time: u64,
 }
 
 #[cw_serde]
@@ -229,6 +333,19 @@ pub struct AllNftInfoResponse<T> {
     // pub mode:Option<String>,
 }
 
+#[cw_serde]
+pub struct RoyaltiesInfoResponse {
+    /// Receiver of the royalty, empty when the token has none
+    pub address: String,
+    /// Royalty owed on the queried sale price, in the same denom
+    pub royalty_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
 #[cw_serde]
 pub struct TokensResponse {
     /// Contains all token_ids in lexicographical ordering
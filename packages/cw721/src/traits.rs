@@ -2,11 +2,13 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::{
-    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, LongTermRental,
-    NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
-    ShortTermRental, TokensResponse,
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, CheckRoyaltiesResponse,
+    ContractInfoResponse, LongTermRental, NftInfoResponse, NumTokensResponse, OperatorResponse,
+    OperatorsResponse, OwnerOfResponse, RoyaltiesInfoResponse, ShortTermRental, TokensResponse,
+};
+use cosmwasm_std::{
+    Binary, CustomMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
 };
-use cosmwasm_std::{Binary, CustomMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 use cw_utils::Expiration;
 
 pub trait Cw721<T, C>: Cw721Execute<T, C> + Cw721Query<T>
@@ -171,4 +173,13 @@ where
         token_id: String,
         include_expired: bool,
     ) -> StdResult<AllNftInfoResponse<T>>;
+
+    fn royalty_info(
+        &self,
+        deps: Deps,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltiesInfoResponse>;
+
+    fn check_royalties(&self, deps: Deps) -> StdResult<CheckRoyaltiesResponse>;
 }